@@ -12,6 +12,8 @@ pub fn possible_action_kind(game: &impl Game, position: Position) -> Vec<ActionK
 
     !!! Не могу сыграть рейз или оверколл-алын, если все вокруг меня в алине. Только колл или фолд.
         Это важно для рассчета пота для рейка.
+    - В безлимитном варианте сайзинги те же, но банк не ограничивает рейз: если стек больше пота,
+      то последним добавляется рейз-алын.
      */
 
     if game.folded_positions().contains(&position) {
@@ -34,7 +36,8 @@ pub fn possible_action_kind(game: &impl Game, position: Position) -> Vec<ActionK
     let can_full_call = can_full_call(game, position, max_commit);
     let can_over_full_call_alin = can_over_full_call_alin(game, position, max_commit);
 
-    let old_pot = game.main_pot().value;
+    // Сайзинги считаются от банка, который игрок может выиграть, а не от общего с чужими сайд-потами.
+    let old_pot = game.winnable_pot(position);
     let size_pot_raise = max_commit + (dec!(1) * (old_pot - my_commit + max_commit)).round_dp(0);
     let size_75_raise = max_commit + (dec!(0.75) * (old_pot - my_commit + max_commit)).round_dp(0);
     let size_50_raise = max_commit + (dec!(0.5) * (old_pot - my_commit + max_commit)).round_dp(0);
@@ -62,6 +65,9 @@ pub fn possible_action_kind(game: &impl Game, position: Position) -> Vec<ActionK
             possible_acts.push(ActionKind::Raise(size_pot_raise));
             possible_acts.push(ActionKind::Raise(size_75_raise));
             possible_acts.push(ActionKind::Raise(size_50_raise));
+            if game.table().variant().is_no_limit() && player.stack_size > size_pot_raise {
+                possible_acts.push(ActionKind::Raise(player.stack_size));
+            }
         } else if can_75_size_raise {
             possible_acts.push(ActionKind::Raise(player.stack_size)); // ???
             possible_acts.push(ActionKind::Raise(size_75_raise));
@@ -120,12 +126,12 @@ pub fn can_raise_by_stack_and_action(
     нельзя рейзить.
     + Кроме первого действующего на первом кругу, или первого действующего после чеков перед ним постфлоп!
     НО нет если на первом кругу чел, а остальные в алине или фолде.
-    + Кроме ВВ и SB на первом кругу ставок префлоп!
+    + Кроме блайндов и страддлера на первом кругу ставок префлоп (вложили ровно свой блайнд)!
     2. Если после кола предыдущего рейза, ты можешь повысить на min_bet и выше.
      */
     let can_raise_besides_action = if game.is_preflop_game() {
-        (position == Position::Bb && already_commit == dec!(1))
-            || (position == Position::Sb && already_commit == dec!(0.5))
+        let posted = game.table().posted_blind(position);
+        posted > Decimal::ZERO && already_commit == posted
     } else {
         max_commit == Decimal::ZERO
             && already_commit == Decimal::ZERO
@@ -153,9 +159,13 @@ pub fn can_check(
 ) -> bool {
     /* Расширяет preflop_action_calc.rs.
     Чек на префлопе возможен только, если до тебя были только чеки.
+    Чекнуть может только последний префлоп: ББ, а со страддлом страддлер.
      */
     if game.is_preflop_game() {
-        (position == Position::Bb && already_commit == dec!(1)) && (already_commit == max_commit)
+        let table = game.table();
+        table.preflop_order().last() == Some(&position)
+            && already_commit == table.posted_blind(position)
+            && already_commit == max_commit
     } else {
         max_commit == Decimal::ZERO && already_commit == Decimal::ZERO
     }
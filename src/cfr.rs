@@ -0,0 +1,583 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use rand::distributions::{Distribution, WeightedIndex};
+
+use crate::{GameRng, Node};
+
+/*
+Одна раздача ривера хедз-ап для тренировки CFR.
+- keys: информационные множества игроков, keys[0] - кто ходит первым (его ноды B50, B75, B100, X),
+  keys[1] - второй. В main это FakePostflopNew каждого игрока.
+- payoffs: результат обоих игроков в больших блайндах для каждой последней ноды ветки
+  (Branch::last_node).
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiverDeal<K> {
+    pub keys: [K; 2],
+    pub payoffs: HashMap<Node, [f64; 2]>,
+}
+
+// Доли действий в точке решения.
+pub type Strategy = Vec<(Node, f64)>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct InfoSet {
+    actions: Vec<Node>,
+    regrets: Vec<f64>,
+    strategy_sum: Vec<f64>,
+}
+impl InfoSet {
+    fn new(actions: Vec<Node>) -> Self {
+        let len = actions.len();
+        Self {
+            actions,
+            regrets: vec![0.0; len],
+            strategy_sum: vec![0.0; len],
+        }
+    }
+    // Regret matching: доли положительных сожалений, если их нет - поровну.
+    fn strategy(&self) -> Vec<f64> {
+        let sum = self.regrets.iter().sum::<f64>();
+        if sum > 0.0 {
+            self.regrets.iter().map(|r| r / sum).collect()
+        } else {
+            vec![1.0 / self.actions.len() as f64; self.actions.len()]
+        }
+    }
+    fn average_strategy(&self) -> Vec<f64> {
+        let sum = self.strategy_sum.iter().sum::<f64>();
+        if sum > 0.0 {
+            self.strategy_sum.iter().map(|s| s / sum).collect()
+        } else {
+            vec![1.0 / self.actions.len() as f64; self.actions.len()]
+        }
+    }
+}
+
+/*
+CFR+ по дереву Node/Branch вместо поколений с выбором лучшей ноды (best_node), которые качаются
+между тайтовым и агрессивным поколением.
+- Информационное множество: ключ игрока и предыдущая нода (None - первое действие), как в best_node.
+  Возможные действия - Node::start_nodes() или prev.childrens().
+- Каждая итерация проходит все дерево по всем раздачам. Сожаления не бывают меньше нуля (CFR+),
+  средняя стратегия копится с весом номера итерации. Сходится именно средняя стратегия.
+*/
+#[derive(Debug, Clone)]
+pub struct CfrTrainer<K> {
+    pub iterations: u64,
+    infosets: HashMap<(K, Option<Node>), InfoSet>,
+    childrens: HashMap<Option<Node>, Vec<Node>>,
+}
+impl<K: Eq + Hash + Clone> Default for CfrTrainer<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<K: Eq + Hash + Clone> CfrTrainer<K> {
+    pub fn new() -> Self {
+        Self {
+            iterations: 0,
+            infosets: HashMap::new(),
+            childrens: tree(),
+        }
+    }
+    pub fn train(&mut self, deals: &[RiverDeal<K>], iterations: u64) {
+        for _ in 0..iterations {
+            self.iterations += 1;
+            for deal in deals {
+                self.cfr(deal, None, 0, [1.0, 1.0]);
+            }
+        }
+    }
+    // Ожидаемый результат обоих игроков из ноды prev. depth - сколько действий уже сделано.
+    fn cfr(
+        &mut self,
+        deal: &RiverDeal<K>,
+        prev: Option<Node>,
+        depth: usize,
+        reach: [f64; 2],
+    ) -> [f64; 2] {
+        let actions = self.childrens[&prev].clone();
+        if actions.is_empty() {
+            let node = prev.unwrap_or_else(|| unreachable!());
+            return *deal
+                .payoffs
+                .get(&node)
+                .unwrap_or_else(|| panic!("Error: no payoff for node {:?}", node));
+        }
+        let player = depth % 2;
+        let key = (deal.keys[player].clone(), prev);
+        let strategy = self
+            .infosets
+            .entry(key.clone())
+            .or_insert_with(|| InfoSet::new(actions.clone()))
+            .strategy();
+
+        let mut utils = Vec::with_capacity(actions.len());
+        let mut node_util = [0.0; 2];
+        for (&action, &share) in actions.iter().zip(strategy.iter()) {
+            let mut next_reach = reach;
+            next_reach[player] *= share;
+            let util = self.cfr(deal, Some(action), depth + 1, next_reach);
+            node_util[0] += share * util[0];
+            node_util[1] += share * util[1];
+            utils.push(util[player]);
+        }
+
+        let weight = self.iterations as f64;
+        let info = self
+            .infosets
+            .get_mut(&key)
+            .unwrap_or_else(|| unreachable!());
+        for (i, util) in utils.into_iter().enumerate() {
+            info.regrets[i] =
+                (info.regrets[i] + reach[1 - player] * (util - node_util[player])).max(0.0);
+            info.strategy_sum[i] += weight * reach[player] * strategy[i];
+        }
+        node_util
+    }
+    // Средняя стратегия в точке решения, None - точка ни разу не встретилась.
+    pub fn average_strategy(&self, key: &K, prev: Option<Node>) -> Option<Strategy> {
+        let info = self.infosets.get(&(key.clone(), prev))?;
+        Some(
+            info.actions
+                .iter()
+                .copied()
+                .zip(info.average_strategy())
+                .collect(),
+        )
+    }
+    pub fn average_strategies(&self) -> HashMap<K, Vec<(Option<Node>, Strategy)>> {
+        let mut result: HashMap<K, Vec<(Option<Node>, Strategy)>> = HashMap::new();
+        for ((key, prev), info) in self.infosets.iter() {
+            let strategy = info
+                .actions
+                .iter()
+                .copied()
+                .zip(info.average_strategy())
+                .collect();
+            result
+                .entry(key.clone())
+                .or_default()
+                .push((*prev, strategy));
+        }
+        for points in result.values_mut() {
+            points.sort_by_key(|(prev, _)| *prev);
+        }
+        result
+    }
+}
+
+// Дети нод из river_tree, чтобы не ходить в общее дерево на каждом шаге.
+fn tree() -> HashMap<Option<Node>, Vec<Node>> {
+    let nodes = Node::all();
+    let mut childrens = HashMap::with_capacity(nodes.len());
+    childrens.insert(None, Node::start_nodes());
+    for node in nodes.into_iter().filter(|&node| node != Node::root()) {
+        childrens.insert(Some(node), node.childrens());
+    }
+    childrens
+}
+
+/*
+Лучший ответ на стратегию по раздачам ривера. Все значения - больших блайндов за раздачу,
+индекс - место (0 ходит первым).
+- best_response: сколько выигрывает идеальная контр-стратегия на этом месте, которая знает
+  стратегию соперника, но не его карты (решает по своему ключу и ноде, как и стратегия).
+- strategy_value: сколько выигрывает сама стратегия на этом месте, играя против себя.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BestResponse {
+    pub deals: usize,
+    pub best_response: [f64; 2],
+    pub strategy_value: [f64; 2],
+}
+impl BestResponse {
+    // Насколько лучший ответ выигрывает больше стратегии, в среднем по местам. В равновесии 0.
+    pub fn exploitability(&self) -> f64 {
+        (self.best_response[0] - self.strategy_value[0] + self.best_response[1]
+            - self.strategy_value[1])
+            / 2.0
+    }
+    pub fn exploitability_bb_100(&self) -> f64 {
+        self.exploitability() * 100.0
+    }
+}
+
+/*
+strategy(ключ, предыдущая нода) - частоты действий игрока. Действия, которых там нет, не играются,
+пустая стратегия (ключ не встречался) - все действия поровну.
+Точка решения лучшего ответа - его ключ и нода, а нода однозначно задает всю ветку до нее. Поэтому
+для каждого ключа листья суммируются с весом вероятности соперника дойти до них, и в своих нодах
+лучший ответ берет максимум, а в нодах соперника - сумму.
+*/
+pub fn best_response<K, F>(deals: &[RiverDeal<K>], strategy: F) -> BestResponse
+where
+    K: Eq + Hash + Clone,
+    F: Fn(&K, Option<Node>) -> Strategy,
+{
+    let tree = tree();
+    let mut result = BestResponse {
+        deals: deals.len(),
+        best_response: [0.0; 2],
+        strategy_value: [0.0; 2],
+    };
+    if deals.is_empty() {
+        return result;
+    }
+    for player in 0..2 {
+        let mut leaves_by_key: HashMap<&K, HashMap<Node, f64>> = HashMap::new();
+        for deal in deals {
+            let leaves = leaves_by_key.entry(&deal.keys[player]).or_default();
+            // (нода, сколько действий сделано, вероятность соперника, вероятность обоих)
+            let mut stack = vec![(None, 0, 1.0, 1.0)];
+            while let Some((prev, depth, opp_reach, reach)) = stack.pop() {
+                let actions = &tree[&prev];
+                if actions.is_empty() {
+                    let node = prev.unwrap_or_else(|| unreachable!());
+                    let payoff = deal.payoffs[&node];
+                    *leaves.entry(node).or_insert(0.0) += opp_reach * payoff[player];
+                    if player == 0 {
+                        result.strategy_value[0] += reach * payoff[0];
+                        result.strategy_value[1] += reach * payoff[1];
+                    }
+                    continue;
+                }
+                let actor = depth % 2;
+                let probs = probabilities(&strategy(&deal.keys[actor], prev), actions);
+                for (&action, share) in actions.iter().zip(probs) {
+                    let next_opp_reach = if actor == player {
+                        opp_reach
+                    } else {
+                        opp_reach * share
+                    };
+                    stack.push((Some(action), depth + 1, next_opp_reach, reach * share));
+                }
+            }
+        }
+        result.best_response[player] = leaves_by_key
+            .values()
+            .map(|leaves| best_response_value(&tree, leaves, None, 0, player))
+            .sum::<f64>()
+            / deals.len() as f64;
+    }
+    result.strategy_value[0] /= deals.len() as f64;
+    result.strategy_value[1] /= deals.len() as f64;
+    result
+}
+fn best_response_value(
+    tree: &HashMap<Option<Node>, Vec<Node>>,
+    leaves: &HashMap<Node, f64>,
+    prev: Option<Node>,
+    depth: usize,
+    player: usize,
+) -> f64 {
+    let actions = &tree[&prev];
+    if actions.is_empty() {
+        let node = prev.unwrap_or_else(|| unreachable!());
+        return leaves.get(&node).copied().unwrap_or(0.0);
+    }
+    let values = actions
+        .iter()
+        .map(|&action| best_response_value(tree, leaves, Some(action), depth + 1, player));
+    if depth % 2 == player {
+        values.fold(f64::MIN, f64::max)
+    } else {
+        values.sum()
+    }
+}
+/*
+Матч двух стратегий по раздачам ривера с дублированием: каждая раздача играется дважды с теми же
+картами, во второй раз стратегии меняются местами. Результат раздачи для first - среднее двух
+розыгрышей, поэтому карты и место почти не добавляют разброса. Розыгрыш считается точно по
+частотам (ожидание по всем веткам), разброс остается только от выбора раздач.
+- bb_100: выигрыш first в BB/100, ci_95: половина 95% доверительного интервала (bb_100 ± ci_95).
+  Для одной раздачи интервал не считается - бесконечность.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchResult {
+    pub deals: usize,
+    pub bb_100: f64,
+    pub ci_95: f64,
+}
+impl MatchResult {
+    // Ноль вне доверительного интервала: одна стратегия действительно сильнее.
+    pub fn is_significant(&self) -> bool {
+        self.bb_100.abs() > self.ci_95
+    }
+}
+pub fn head_to_head<K, A, B>(deals: &[RiverDeal<K>], first: A, second: B) -> MatchResult
+where
+    K: Eq + Hash + Clone,
+    A: Fn(&K, Option<Node>) -> Strategy,
+    B: Fn(&K, Option<Node>) -> Strategy,
+{
+    let tree = tree();
+    let results = deals
+        .iter()
+        .map(|deal| {
+            let first_seat = expected_value(&tree, deal, [&first, &second])[0];
+            let second_seat = expected_value(&tree, deal, [&second, &first])[1];
+            (first_seat + second_seat) / 2.0
+        })
+        .collect::<Vec<f64>>();
+    let n = results.len() as f64;
+    let mean = if results.is_empty() {
+        0.0
+    } else {
+        results.iter().sum::<f64>() / n
+    };
+    let ci_95 = if results.len() < 2 {
+        f64::INFINITY
+    } else {
+        let variance = results.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        1.96 * (variance / n).sqrt()
+    };
+    MatchResult {
+        deals: results.len(),
+        bb_100: mean * 100.0,
+        ci_95: ci_95 * 100.0,
+    }
+}
+type StrategyFn<'a, K> = dyn Fn(&K, Option<Node>) -> Strategy + 'a;
+
+// Ожидаемый результат мест раздачи, strategies[0] ходит первой.
+fn expected_value<K>(
+    tree: &HashMap<Option<Node>, Vec<Node>>,
+    deal: &RiverDeal<K>,
+    strategies: [&StrategyFn<'_, K>; 2],
+) -> [f64; 2] {
+    let mut result = [0.0; 2];
+    let mut stack = vec![(None, 0, 1.0)];
+    while let Some((prev, depth, reach)) = stack.pop() {
+        let actions = &tree[&prev];
+        if actions.is_empty() {
+            let payoff = deal.payoffs[&prev.unwrap_or_else(|| unreachable!())];
+            result[0] += reach * payoff[0];
+            result[1] += reach * payoff[1];
+            continue;
+        }
+        let actor = depth % 2;
+        let probs = probabilities(&strategies[actor](&deal.keys[actor], prev), actions);
+        for (&action, share) in actions.iter().zip(probs) {
+            if share > 0.0 {
+                stack.push((Some(action), depth + 1, reach * share));
+            }
+        }
+    }
+    result
+}
+fn probabilities(strategy: &[(Node, f64)], actions: &[Node]) -> Vec<f64> {
+    let freqs = actions
+        .iter()
+        .map(|action| {
+            strategy
+                .iter()
+                .find(|(node, _)| node == action)
+                .map_or(0.0, |(_, freq)| *freq)
+        })
+        .collect::<Vec<f64>>();
+    let sum = freqs.iter().sum::<f64>();
+    if sum > 0.0 {
+        freqs.into_iter().map(|freq| freq / sum).collect()
+    } else {
+        vec![1.0 / actions.len() as f64; actions.len()]
+    }
+}
+
+/*
+Частоты действий по винрейтам, когда сожалений из CFR нет (графы поколений, записи в редиске).
+winrates: (винрейт, число рук) каждого действия точки решения.
+- Сожаление действия - насколько его винрейт выше среднего по точке. Средний берется с весом рук,
+  то есть это результат того, как точку играли на самом деле.
+- Частоты пропорциональны положительным сожалениям (regret matching, как в CfrTrainer).
+- Лучше среднего ничего нет - все на лучшее действие. Рук нет совсем - поровну.
+*/
+pub fn frequencies_from_winrates(winrates: &[(f64, f64)]) -> Vec<f64> {
+    let hands = winrates.iter().map(|(_, hands)| hands).sum::<f64>();
+    if winrates.is_empty() || hands <= 0.0 {
+        return vec![1.0 / winrates.len() as f64; winrates.len()];
+    }
+    let average = winrates.iter().map(|(wr, hands)| wr * hands).sum::<f64>() / hands;
+    let regrets = winrates
+        .iter()
+        .map(|(wr, _)| (wr - average).max(0.0))
+        .collect::<Vec<f64>>();
+    let sum = regrets.iter().sum::<f64>();
+    if sum > 0.0 {
+        return regrets.into_iter().map(|r| r / sum).collect();
+    }
+    let best = winrates
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| b.0.total_cmp(&a.0)) // При равенстве первое.
+        .map(|(i, _)| i)
+        .unwrap_or_else(|| unreachable!());
+    (0..winrates.len())
+        .map(|i| if i == best { 1.0 } else { 0.0 })
+        .collect()
+}
+
+// Случайное действие по частотам. None - нет действий или все частоты нулевые.
+pub fn sample<T: Copy>(distribution: &[(T, f64)], rng: &mut GameRng) -> Option<T> {
+    let index = WeightedIndex::new(distribution.iter().map(|(_, freq)| *freq)).ok()?;
+    Some(distribution[index.sample(rng)].0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{seeded_rng, Branch};
+
+    /*
+    Игра с блефом: первый с сильной или слабой рукой ставит пот или чекает, второй на ставку
+    коллирует или фолдит. Банк 1 (по 0.5 от каждого), ставка 1. Остальные ветки заведомо плохие
+    для того, кто их выбирает. Равновесие: слабая рука блефует в половине случаев, второй коллирует
+    в половине случаев.
+    */
+    fn bluff_deal(strong: bool) -> RiverDeal<&'static str> {
+        let sign = if strong { 1.0 } else { -1.0 };
+        let payoffs = Branch::all_branches()
+            .into_iter()
+            .map(|branch| {
+                let first = match branch.last_node().name() {
+                    "B100F" => 0.5,
+                    "B100C" => 1.5 * sign,
+                    "XX" => 0.5 * sign,
+                    _ if branch.path.contains(&node("B100R")) => 5.0,
+                    _ if branch.first_node() == node("X") => 5.0,
+                    _ => -5.0,
+                };
+                (branch.last_node(), [first, -first])
+            })
+            .collect();
+        RiverDeal {
+            keys: [if strong { "strong" } else { "weak" }, "second"],
+            payoffs,
+        }
+    }
+    fn node(name: &str) -> Node {
+        name.parse().unwrap()
+    }
+    fn share(strategy: &[(Node, f64)], node: Node) -> f64 {
+        strategy.iter().find(|(n, _)| *n == node).unwrap().1
+    }
+    #[test]
+    fn cfr_converges_to_bluff_equilibrium() {
+        let deals = [bluff_deal(true), bluff_deal(false)];
+        let mut trainer = CfrTrainer::new();
+        trainer.train(&deals, 2_000);
+        assert_eq!(trainer.iterations, 2_000);
+
+        let strong = trainer.average_strategy(&"strong", None).unwrap();
+        assert!(share(&strong, node("B100")) > 0.95);
+        let weak = trainer.average_strategy(&"weak", None).unwrap();
+        assert!((share(&weak, node("B100")) - 0.5).abs() < 0.05);
+        assert!(share(&weak, node("B50")) < 0.01);
+        let call = trainer
+            .average_strategy(&"second", Some(node("B100")))
+            .unwrap();
+        assert!((share(&call, node("B100C")) - 0.5).abs() < 0.05);
+        assert!(share(&call, node("B100R")) < 0.01);
+        // После чека второй всегда чекает в ответ.
+        let after_check = trainer
+            .average_strategy(&"second", Some(node("X")))
+            .unwrap();
+        assert!(share(&after_check, node("XX")) > 0.95);
+
+        assert!(trainer
+            .average_strategy(&"weak", Some(node("B50")))
+            .is_none());
+        let all = trainer.average_strategies();
+        // Первый решает в начале, после рейза на свою ставку и после ставки на свой чек.
+        assert_eq!(all[&"strong"].len(), 1 + 3 + 6);
+    }
+    #[test]
+    fn frequencies_and_sampling() {
+        // Средний винрейт 0.2 (с весом рук), выше него только 1.0 и 0.6.
+        let freqs = frequencies_from_winrates(&[(1.0, 10.0), (0.6, 10.0), (-0.4, 20.0)]);
+        assert!((freqs[0] - 2.0 / 3.0).abs() < 1e-9);
+        assert!((freqs[1] - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(freqs[2], 0.0);
+        assert_eq!(
+            frequencies_from_winrates(&[(0.5, 10.0), (0.5, 30.0)]),
+            vec![1.0, 0.0]
+        );
+        assert_eq!(
+            frequencies_from_winrates(&[(0.0, 0.0), (0.0, 0.0)]),
+            vec![0.5, 0.5]
+        );
+
+        let distribution = [(node("B100"), 0.25), (node("X"), 0.75), (node("B50"), 0.0)];
+        let mut rng = seeded_rng(3);
+        let picks = (0..4_000)
+            .map(|_| sample(&distribution, &mut rng).unwrap())
+            .collect::<Vec<Node>>();
+        let bets = picks
+            .iter()
+            .filter(|&&picked| picked == node("B100"))
+            .count();
+        assert!((900..1100).contains(&bets));
+        assert!(!picks.contains(&node("B50")));
+        // Тот же сид - те же решения.
+        let mut rng = seeded_rng(3);
+        assert_eq!(sample(&distribution, &mut rng), Some(picks[0]));
+        assert_eq!(sample(&[(node("X"), 0.0)], &mut rng), None);
+    }
+    #[test]
+    fn best_response_to_pure_and_trained_strategies() {
+        let deals = [bluff_deal(true), bluff_deal(false)];
+        // Первый всегда ставит пот, второй на ставку всегда фолдит: второму выгоднее коллировать.
+        let always_bet = |key: &&str, prev: Option<Node>| match (*key, prev.map(|n| n.name())) {
+            (_, None) => vec![(node("B100"), 1.0)],
+            ("second", Some("B100")) => vec![(node("B100F"), 1.0)],
+            ("second", Some("X")) => vec![(node("XX"), 1.0)],
+            _ => vec![],
+        };
+        let result = best_response(&deals, always_bet);
+        assert_eq!(result.deals, 2);
+        assert!((result.strategy_value[0] - 0.5).abs() < 1e-9);
+        assert!((result.strategy_value[1] + 0.5).abs() < 1e-9);
+        assert!((result.best_response[0] - 0.5).abs() < 1e-9);
+        assert!(result.best_response[1].abs() < 1e-9);
+        assert!((result.exploitability_bb_100() - 25.0).abs() < 1e-6);
+
+        let mut trainer = CfrTrainer::new();
+        trainer.train(&deals, 2_000);
+        let trained = best_response(&deals, |key, prev| {
+            trainer.average_strategy(key, prev).unwrap_or_default()
+        });
+        assert!(trained.exploitability_bb_100() < 1.0);
+        assert!(trained.exploitability() >= -1e-9);
+    }
+    #[test]
+    fn duplicate_match_between_strategies() {
+        let deals = [bluff_deal(true), bluff_deal(false)];
+        let mut trainer = CfrTrainer::new();
+        trainer.train(&deals, 2_000);
+        let trained = |key: &&str, prev| trainer.average_strategy(key, prev).unwrap_or_default();
+        // Первым всегда ставит полбанка (-5), вторым на ставку в пот фолдит.
+        let bad = |_: &&str, prev: Option<Node>| match prev.map(|n| n.name()) {
+            None => vec![(node("B50"), 1.0)],
+            Some("B100") => vec![(node("B100F"), 1.0)],
+            Some("X") => vec![(node("XX"), 1.0)],
+            _ => vec![],
+        };
+
+        let itself = head_to_head(&deals, trained, trained);
+        assert!(itself.bb_100.abs() < 1e-9);
+        assert!(itself.ci_95.abs() < 1e-9);
+
+        // Сильная раздача: (0.5 + 5) / 2, слабая: (0 + 5) / 2.
+        let result = head_to_head(&deals, trained, bad);
+        assert_eq!(result.deals, 2);
+        assert!((result.bb_100 - 262.5).abs() < 3.0);
+        assert!((result.ci_95 - 24.5).abs() < 2.0);
+        assert!(result.is_significant());
+        let mirrored = head_to_head(&deals, bad, trained);
+        assert!((mirrored.bb_100 + result.bb_100).abs() < 1e-9);
+
+        assert!(head_to_head(&deals[..1], bad, trained).ci_95.is_infinite());
+    }
+}
@@ -0,0 +1,383 @@
+use rand::SeedableRng;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::{
+    action,
+    eval_hand::{real_low, showdown_strength, HandStrength, LowHand},
+    eval_result,
+    history::HandHistory,
+    seeded_rng, ActionKind, Card, Game, GameRng, Position, PostflopGame, PreflopGame, ReadyHand,
+    TableConfig,
+};
+
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum Street {
+    Preflop,
+    Flop,
+    Turn,
+    River,
+}
+impl Street {
+    pub fn next(&self) -> Option<Self> {
+        match self {
+            Self::Preflop => Some(Self::Flop),
+            Self::Flop => Some(Self::Turn),
+            Self::Turn => Some(Self::River),
+            Self::River => None,
+        }
+    }
+}
+
+pub enum StreetGame<'a> {
+    Preflop(&'a PreflopGame),
+    Postflop(&'a PostflopGame),
+}
+pub trait AsStreetGame {
+    fn as_street_game(&self) -> StreetGame<'_>;
+}
+impl AsStreetGame for PreflopGame {
+    fn as_street_game(&self) -> StreetGame<'_> {
+        StreetGame::Preflop(self)
+    }
+}
+impl AsStreetGame for PostflopGame {
+    fn as_street_game(&self) -> StreetGame<'_> {
+        StreetGame::Postflop(self)
+    }
+}
+
+// Точка принятия решения. possible_act всегда не пустой.
+pub struct DecisionPoint<'a> {
+    pub street: Street,
+    pub position: Position,
+    pub possible_act: &'a Vec<ActionKind>,
+    pub game: StreetGame<'a>,
+}
+
+pub struct HandOutcome {
+    pub win_loose: HashMap<Position, Decimal>,
+    pub last_street: Street,
+    pub board: Vec<Card>,
+    // Только позиции, которые дошли до вскрытия.
+    pub real_hands_end: HashMap<Position, ReadyHand>,
+    // Только в хай-лоу и только позиции, у которых есть лоу.
+    pub low_hands_end: HashMap<Position, LowHand>,
+    pub history: HandHistory,
+}
+
+/*
+- Играет раздачу от префлопа до вскрытия. Решение на каждой позиции принимает колбэк decide,
+  он же решает чей это бот, сеть или рандом (смотри position в DecisionPoint).
+- Правила остановки улицы и раздачи собраны здесь, а не в каждом месте, где играется рука:
+    1. Все кроме одного сфолдили - конец раздачи.
+    2. Постфлоп все, кто мог, чекнули - конец улицы.
+    3. Все в фолде/алине - конец улицы, дальше просто раздаются карты до ривера.
+- Карты раздаются из rng движка. with_seed дает повторяемые раздачи, new - случайные.
+  Если decide выбирает действия рандомно, то ему нужен свой сид (ActionKind::rnd_action_from_with).
+- decide вернул None - позиция сдается: фолд, а если можно чекнуть, то чек (фолда тогда нет).
+- play_street играет одну улицу с уже готовой игры: так main играет синтетические раздачи ривера,
+  для них истории нет (префлопа не было).
+*/
+pub struct HandEngine<F>
+where
+    F: FnMut(&DecisionPoint) -> Option<ActionKind>,
+{
+    decide: F,
+    rng: GameRng,
+    history: Option<HandHistory>,
+}
+impl<F> HandEngine<F>
+where
+    F: FnMut(&DecisionPoint) -> Option<ActionKind>,
+{
+    pub fn new(decide: F) -> Self {
+        Self {
+            decide,
+            rng: GameRng::from_entropy(),
+            history: None,
+        }
+    }
+    pub fn with_seed(decide: F, seed: u64) -> Self {
+        Self {
+            decide,
+            rng: seeded_rng(seed),
+            history: None,
+        }
+    }
+    pub fn play_new_hand(&mut self, table: TableConfig) -> HandOutcome {
+        let preflop_game = PreflopGame::new_with_table(table, &vec![], &mut self.rng);
+        self.play(preflop_game)
+    }
+    pub fn play(&mut self, mut preflop_game: PreflopGame) -> HandOutcome {
+        self.history = Some(HandHistory::new(&preflop_game));
+        self.play_street(&mut preflop_game, Street::Preflop);
+        if preflop_game.end_of_hand_five_foldes() {
+            return self.outcome(
+                &preflop_game,
+                Street::Preflop,
+                HashMap::new(),
+                HashMap::new(),
+            );
+        }
+        let mut postflop_game = PostflopGame::from_with_rng(&preflop_game, &mut self.rng);
+        self.record_board(Street::Flop, &[], &postflop_game.cards);
+        let mut street = Street::Flop;
+        loop {
+            self.play_street(&mut postflop_game, street);
+            if postflop_game.end_of_hand_five_foldes() {
+                return self.outcome(&postflop_game, street, HashMap::new(), HashMap::new());
+            }
+            match street.next() {
+                Some(next) => {
+                    let prev_cards = postflop_game.cards.clone();
+                    postflop_game = PostflopGame::from_with_rng(&postflop_game, &mut self.rng);
+                    self.record_board(next, &prev_cards, &postflop_game.cards);
+                    street = next;
+                }
+                None => break,
+            }
+        }
+        let variant = postflop_game.table.variant();
+        let mut real_hands_end = HashMap::new();
+        let mut low_hands_end = HashMap::new();
+        for pos in postflop_game.table.positions() {
+            if postflop_game.folded_positions.contains(&pos) {
+                continue;
+            }
+            let player = postflop_game.player_by_position_as_ref(pos);
+            real_hands_end.insert(
+                pos,
+                showdown_strength(variant, &player.hand, &postflop_game.cards),
+            );
+            if variant.is_hi_lo() {
+                if let Some(low) = real_low(&player.hand, &postflop_game.cards) {
+                    low_hands_end.insert(pos, low);
+                }
+            }
+        }
+        self.outcome(&postflop_game, Street::River, real_hands_end, low_hands_end)
+    }
+    pub fn play_street<G: Game + AsStreetGame>(&mut self, game: &mut G, street: Street) {
+        let poses = street_order(game.table(), street);
+        let mut cyrcle_count = 0_u8;
+        for &position in poses.iter().cycle() {
+            if position == poses[0] {
+                cyrcle_count += 1;
+            }
+            if !game.folded_positions().contains(&position) && game.end_of_hand_five_foldes() {
+                break;
+            }
+            // Если все кто мог сделать экшн чекнули на постфлопе, то заканчиваем улицу.
+            if street != Street::Preflop && cyrcle_count > 1 && game.no_money_in_game() {
+                break;
+            }
+            let possible_act = action::possible_action_kind(game, position);
+            if game.end_of_street(&possible_act, position) {
+                break;
+            }
+            if possible_act.is_empty() {
+                // Позиция в фолде или алине, решения нет.
+                continue;
+            }
+            let choosen_act = {
+                let point = DecisionPoint {
+                    street,
+                    position,
+                    possible_act: &possible_act,
+                    game: game.as_street_game(),
+                };
+                (self.decide)(&point).unwrap_or_else(|| give_up(&possible_act))
+            };
+            let pot_before = game.main_pot().value;
+            game.do_action_on_position(Some(choosen_act), position);
+            if let Some(history) = self.history.as_mut() {
+                history.push_action(
+                    street,
+                    position,
+                    choosen_act,
+                    pot_before,
+                    game.main_pot().value,
+                );
+            }
+        }
+    }
+    fn record_board(&mut self, street: Street, prev_cards: &[Card], cards: &[Card]) {
+        if let Some(history) = self.history.as_mut() {
+            history.push_board(street, prev_cards, cards);
+        }
+    }
+    fn outcome(
+        &mut self,
+        game: &impl Game,
+        last_street: Street,
+        real_hands_end: HashMap<Position, HandStrength>,
+        low_hands_end: HashMap<Position, LowHand>,
+    ) -> HandOutcome {
+        // Банк делится по числовой силе: она, в отличие от ReadyHand, различает кикер у каре.
+        let win_loose = if game.table().variant().is_hi_lo() {
+            eval_result::eval_side_pots_hi_lo_win_loose(game, &real_hands_end, &low_hands_end)
+        } else {
+            eval_result::eval_side_pots_win_loose(game, &real_hands_end)
+        };
+        // play всегда начинает запись, поэтому история здесь есть.
+        let mut history = self.history.take().unwrap_or_else(|| unreachable!());
+        history.win_loose = win_loose.clone();
+        HandOutcome {
+            win_loose,
+            last_street,
+            board: game.cards().cloned().unwrap_or_default(),
+            real_hands_end: real_hands_end
+                .into_iter()
+                .map(|(pos, strength)| (pos, ReadyHand::from(strength)))
+                .collect(),
+            low_hands_end,
+            history,
+        }
+    }
+}
+
+fn give_up(possible_act: &[ActionKind]) -> ActionKind {
+    if possible_act.contains(&ActionKind::Check) {
+        ActionKind::Check
+    } else {
+        ActionKind::Fold
+    }
+}
+
+pub fn street_order(table: TableConfig, street: Street) -> Vec<Position> {
+    match street {
+        Street::Preflop => table.preflop_order(),
+        _ => table.postflop_order(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameVariant;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn all_fold_to_bb() {
+        let mut engine = HandEngine::new(|point: &DecisionPoint| {
+            if point.possible_act.contains(&ActionKind::Fold) {
+                Some(ActionKind::Fold)
+            } else {
+                point.possible_act.first().copied()
+            }
+        });
+        let outcome = engine.play(PreflopGame::new());
+        assert_eq!(outcome.last_street, Street::Preflop);
+        assert!(outcome.real_hands_end.is_empty());
+        assert_eq!(outcome.win_loose.get(&Position::Bb), Some(&dec!(0.5)));
+        assert_eq!(outcome.win_loose.get(&Position::Sb), Some(&dec!(-0.5)));
+        assert_eq!(outcome.win_loose.values().sum::<Decimal>(), Decimal::ZERO);
+    }
+    #[test]
+    fn no_decision_gives_up() {
+        // Без решений все сдаются: префлоп фолд до Bb, раздача не зависает.
+        let mut engine = HandEngine::with_seed(|_: &DecisionPoint| None, 3);
+        let outcome = engine.play_new_hand(TableConfig::default());
+        assert_eq!(outcome.last_street, Street::Preflop);
+        assert_eq!(outcome.win_loose.get(&Position::Bb), Some(&dec!(0.5)));
+        assert!(outcome
+            .history
+            .actions
+            .iter()
+            .all(|a| a.action == ActionKind::Fold));
+    }
+    #[test]
+    fn same_seed_same_history() {
+        // Префлоп лимп, на флопе первый ставит, остальные фолдят: до вскрытия не доходит.
+        let play = |seed| {
+            let mut engine = HandEngine::with_seed(
+                |point: &DecisionPoint| {
+                    let find = |f: fn(&ActionKind) -> bool| {
+                        point.possible_act.iter().find(|a| f(a)).copied()
+                    };
+                    match point.street {
+                        Street::Preflop => {
+                            find(|a| matches!(a, ActionKind::Call(_) | ActionKind::Check))
+                        }
+                        _ => find(|a| matches!(a, ActionKind::Raise(_) | ActionKind::Fold)),
+                    }
+                },
+                seed,
+            );
+            engine.play_new_hand(TableConfig::default()).history
+        };
+        assert_eq!(play(7), play(7));
+        assert_ne!(play(7).players, play(8).players);
+    }
+    #[test]
+    fn heads_up_and_full_ring_fold_to_bb() {
+        // В хедз-апе первым префлоп ходит Sb (баттон), на 9-max Utg.
+        for seats in [2, 9] {
+            let table = TableConfig::new(seats).unwrap();
+            let mut first = None;
+            let mut engine = HandEngine::new(|point: &DecisionPoint| {
+                first.get_or_insert(point.position);
+                Some(ActionKind::Fold)
+            });
+            let outcome = engine.play_new_hand(table);
+            assert_eq!(outcome.history.players.len(), seats as usize);
+            assert_eq!(outcome.last_street, Street::Preflop);
+            assert_eq!(outcome.win_loose.get(&Position::Bb), Some(&dec!(0.5)));
+            assert_eq!(outcome.win_loose.len(), seats as usize);
+            assert_eq!(first, Some(table.preflop_order()[0]));
+        }
+    }
+    #[test]
+    fn check_down_to_showdown_all_variants() {
+        // Без файла комбинаций вскрытие работает для любого варианта, банк сходится в ноль.
+        for variant in [
+            GameVariant::Plo4,
+            GameVariant::Plo5,
+            GameVariant::Plo4HiLo,
+            GameVariant::Plo5HiLo,
+        ] {
+            let table = TableConfig::default().with_variant(variant);
+            let mut engine = HandEngine::with_seed(
+                |point: &DecisionPoint| {
+                    point
+                        .possible_act
+                        .iter()
+                        .find(|a| matches!(a, ActionKind::Call(_) | ActionKind::Check))
+                        .copied()
+                },
+                5,
+            );
+            let outcome = engine.play_new_hand(table);
+            assert_eq!(outcome.last_street, Street::River);
+            assert_eq!(outcome.real_hands_end.len(), 6);
+            assert_eq!(outcome.win_loose.values().sum::<Decimal>(), Decimal::ZERO);
+            assert!(variant.is_hi_lo() || outcome.low_hands_end.is_empty());
+        }
+    }
+    #[test]
+    fn holdem_heads_up_to_showdown() {
+        // Все коллят и чекают до ривера, банк делится по лучшим 5 из 7.
+        let table = TableConfig::heads_up().with_variant(GameVariant::Holdem);
+        let mut engine = HandEngine::with_seed(
+            |point: &DecisionPoint| {
+                point
+                    .possible_act
+                    .iter()
+                    .find(|a| matches!(a, ActionKind::Call(_) | ActionKind::Check))
+                    .copied()
+            },
+            11,
+        );
+        let outcome = engine.play_new_hand(table);
+        assert_eq!(outcome.last_street, Street::River);
+        assert!(outcome
+            .history
+            .players
+            .iter()
+            .all(|p| p.hand.cards.len() == 2));
+        assert_eq!(outcome.real_hands_end.len(), 2);
+        assert_eq!(outcome.win_loose.values().sum::<Decimal>(), Decimal::ZERO);
+    }
+}
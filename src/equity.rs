@@ -0,0 +1,402 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use std::collections::HashSet;
+
+use crate::{
+    eval_hand::{real_low, showdown_strength},
+    range::Range,
+    seeded_rng, Card, GameVariant, Hand,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PlayerEquity {
+    pub win: f64,    // Доля бордов, где игрок забрал весь банк.
+    pub tie: f64,    // Доля бордов, где игрок делит банк.
+    pub equity: f64, // Средняя доля банка.
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityResult {
+    pub players: Vec<PlayerEquity>, // В том же порядке, что и руки.
+    pub boards: u64,
+    pub exhaustive: bool,
+}
+
+/*
+Эквити рук друг против друга на борде:
+- Если вариантов добрать борд не больше exhaustive_limit, то перебираются все, иначе Монте-Карло
+  на samples бордов из seed. Один seed - тот же результат.
+- От 2 до 6 рук, в каждой столько карт, сколько в варианте игры. Борд 0, 3, 4 или 5 карт.
+- dead_cards не приходят на борд (сброшенные руки, известные карты).
+- Для хай-лоу банк на каждом борде делится как на вскрытии: половина хаю, половина лоу.
+*/
+pub struct EquityCalc {
+    variant: GameVariant,
+    board: Vec<Card>,
+    dead_cards: Vec<Card>,
+    exhaustive_limit: u64,
+    samples: u64,
+    seed: u64,
+}
+impl EquityCalc {
+    pub fn new(variant: GameVariant) -> Self {
+        Self {
+            variant,
+            board: vec![],
+            dead_cards: vec![],
+            exhaustive_limit: 100_000,
+            samples: 20_000,
+            seed: 0,
+        }
+    }
+    pub fn board(mut self, board: &[Card]) -> Self {
+        self.board = board.to_vec();
+        self
+    }
+    pub fn dead_cards(mut self, cards: &[Card]) -> Self {
+        self.dead_cards = cards.to_vec();
+        self
+    }
+    pub fn exhaustive_limit(mut self, limit: u64) -> Self {
+        self.exhaustive_limit = limit;
+        self
+    }
+    pub fn samples(mut self, samples: u64) -> Self {
+        self.samples = samples;
+        self
+    }
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+    pub fn run(&self, hands: &[Hand]) -> Result<EquityResult, String> {
+        if !(2..=6).contains(&hands.len()) {
+            return Err(format!(
+                "Error: need from 2 to 6 hands, got {}",
+                hands.len()
+            ));
+        }
+        let hole_cards = self.variant.hole_cards();
+        if hands.iter().any(|hand| hand.cards.len() != hole_cards) {
+            return Err(format!("Error: every hand must have {} cards", hole_cards));
+        }
+        self.check_board()?;
+        let used = self.used_cards(hands.iter().flat_map(|hand| hand.cards.iter()))?;
+        let mut deck = Card::deck()
+            .into_iter()
+            .filter(|card| !used.contains(card))
+            .collect::<Vec<Card>>();
+        let missing = 5 - self.board.len();
+        if deck.len() < missing {
+            return Err(String::from("Error: not enough cards left for the board"));
+        }
+
+        let mut tally = Tally::new(hands.len());
+        let mut board = self.board.clone();
+        let mut add_board = |extra: &[Card]| {
+            board.truncate(self.board.len());
+            board.extend_from_slice(extra);
+            tally.add(self.board_shares(hands, &board));
+        };
+
+        let exhaustive =
+            combinations_count(deck.len() as u64, missing as u64) <= self.exhaustive_limit;
+        let boards = if exhaustive {
+            // Перебор сочетаний индексов колоды по возрастанию.
+            let n = deck.len();
+            let mut idx = (0..missing).collect::<Vec<usize>>();
+            let mut extra = Vec::with_capacity(missing);
+            let mut count = 0u64;
+            loop {
+                extra.clear();
+                extra.extend(idx.iter().map(|&i| deck[i]));
+                add_board(&extra);
+                count += 1;
+                let mut i = missing;
+                while i > 0 && idx[i - 1] == n - missing + i - 1 {
+                    i -= 1;
+                }
+                if i == 0 {
+                    break;
+                }
+                idx[i - 1] += 1;
+                for j in i..missing {
+                    idx[j] = idx[j - 1] + 1;
+                }
+            }
+            count
+        } else {
+            let mut rng = seeded_rng(self.seed);
+            for _ in 0..self.samples {
+                let (extra, _) = deck.partial_shuffle(&mut rng, missing);
+                add_board(extra);
+            }
+            self.samples
+        };
+
+        Ok(tally.result(boards, exhaustive))
+    }
+    /*
+    Эквити диапазонов друг против друга, всегда Монте-Карло на samples раздач:
+    - Из диапазонов убираются руки с картами борда и dead_cards.
+    - Руки выбираются по весам по порядку диапазонов. Если рука пересекается с уже розданными,
+      она выбирается заново. Не нашлось руки за 1000 попыток - диапазоны блокируют друг друга.
+    */
+    pub fn run_ranges(&self, ranges: &[Range]) -> Result<EquityResult, String> {
+        if !(2..=6).contains(&ranges.len()) {
+            return Err(format!(
+                "Error: need from 2 to 6 ranges, got {}",
+                ranges.len()
+            ));
+        }
+        if self.variant.hole_cards() != 4 {
+            return Err(String::from("Error: ranges are only for 4 cards hands"));
+        }
+        self.check_board()?;
+        let known = self.used_cards(std::iter::empty())?;
+        let known = known.into_iter().collect::<Vec<Card>>();
+        let ranges = ranges
+            .iter()
+            .map(|range| range.without_blocked(&known))
+            .collect::<Vec<Range>>();
+        if ranges.iter().any(|range| range.is_empty()) {
+            return Err(String::from("Error: range is empty after board blocking"));
+        }
+        let combos = ranges
+            .iter()
+            .map(|range| range.combos.iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let weights = combos
+            .iter()
+            .map(|combos| {
+                WeightedIndex::new(combos.iter().map(|(_, &weight)| weight))
+                    .unwrap_or_else(|_| unreachable!())
+            })
+            .collect::<Vec<_>>();
+
+        let missing = 5 - self.board.len();
+        let mut rng = seeded_rng(self.seed);
+        let mut tally = Tally::new(ranges.len());
+        let mut board = self.board.clone();
+        for _ in 0..self.samples {
+            let mut hands: Vec<Hand> = Vec::with_capacity(ranges.len());
+            for (combos, weights) in combos.iter().zip(weights.iter()) {
+                let hand = (0..1000)
+                    .map(|_| combos[weights.sample(&mut rng)].0)
+                    .find(|hand| {
+                        hands
+                            .iter()
+                            .all(|other| hand.cards.iter().all(|c| !other.cards.contains(c)))
+                    })
+                    .ok_or_else(|| String::from("Error: ranges block each other"))?;
+                hands.push(hand.clone());
+            }
+            let mut deck = Card::deck()
+                .into_iter()
+                .filter(|card| {
+                    !known.contains(card) && hands.iter().all(|hand| !hand.cards.contains(card))
+                })
+                .collect::<Vec<Card>>();
+            let (extra, _) = deck.partial_shuffle(&mut rng, missing);
+            board.truncate(self.board.len());
+            board.extend_from_slice(extra);
+            tally.add(self.board_shares(&hands, &board));
+        }
+        Ok(tally.result(self.samples, false))
+    }
+    fn check_board(&self) -> Result<(), String> {
+        if ![0, 3, 4, 5].contains(&self.board.len()) {
+            return Err(format!(
+                "Error: board must have 0, 3, 4 or 5 cards, got {}",
+                self.board.len()
+            ));
+        }
+        Ok(())
+    }
+    // Карты рук, борда и мертвые. Каждая карта только один раз.
+    fn used_cards<'a>(
+        &'a self,
+        hand_cards: impl Iterator<Item = &'a Card>,
+    ) -> Result<HashSet<Card>, String> {
+        let mut used = HashSet::new();
+        let all_known = hand_cards
+            .chain(self.board.iter())
+            .chain(self.dead_cards.iter());
+        for card in all_known {
+            if !used.insert(*card) {
+                return Err(format!("Error: card {:?} is used twice", card));
+            }
+        }
+        Ok(used)
+    }
+    fn board_shares(&self, hands: &[Hand], board: &[Card]) -> Vec<f64> {
+        // Доля банка каждой руки на полном борде.
+        let mut shares = vec![0f64; hands.len()];
+        let strengths = hands
+            .iter()
+            .map(|hand| showdown_strength(self.variant, hand, board))
+            .collect::<Vec<_>>();
+        let lows = if self.variant.is_hi_lo() {
+            hands
+                .iter()
+                .map(|hand| real_low(hand, board))
+                .collect::<Vec<_>>()
+        } else {
+            vec![None; hands.len()]
+        };
+        let has_low = lows.iter().any(|low| low.is_some());
+        let high_part = if has_low { 0.5 } else { 1.0 };
+        split_to_best(&strengths, high_part, &mut shares);
+        if has_low {
+            split_to_best(&lows, 0.5, &mut shares);
+        }
+        shares
+    }
+}
+struct Tally {
+    shares: Vec<f64>,
+    wins: Vec<u64>,
+    ties: Vec<u64>,
+}
+impl Tally {
+    fn new(players: usize) -> Self {
+        Self {
+            shares: vec![0f64; players],
+            wins: vec![0u64; players],
+            ties: vec![0u64; players],
+        }
+    }
+    fn add(&mut self, board_shares: Vec<f64>) {
+        for (i, share) in board_shares.into_iter().enumerate() {
+            self.shares[i] += share;
+            if share == 1.0 {
+                self.wins[i] += 1;
+            } else if share > 0.0 {
+                self.ties[i] += 1;
+            }
+        }
+    }
+    fn result(self, boards: u64, exhaustive: bool) -> EquityResult {
+        let total = boards as f64;
+        let players = (0..self.shares.len())
+            .map(|i| PlayerEquity {
+                win: self.wins[i] as f64 / total,
+                tie: self.ties[i] as f64 / total,
+                equity: self.shares[i] / total,
+            })
+            .collect();
+        EquityResult {
+            players,
+            boards,
+            exhaustive,
+        }
+    }
+}
+fn split_to_best<T: Ord>(values: &[T], part: f64, shares: &mut [f64]) {
+    let Some(best) = values.iter().max() else {
+        return;
+    };
+    let count = values.iter().filter(|&v| v == best).count() as f64;
+    values
+        .iter()
+        .zip(shares.iter_mut())
+        .filter(|(v, _)| *v == best)
+        .for_each(|(_, share)| *share += part / count);
+}
+fn combinations_count(n: u64, k: u64) -> u64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{cards, hand};
+
+    #[test]
+    fn river_and_turn_are_exhaustive() {
+        let a = hand("As Ah 3d 3c");
+        let b = hand("Ks Kd Qc Jh");
+        let board = cards("Ad Kh 7c 2s 5d");
+        let result = EquityCalc::new(GameVariant::Plo4)
+            .board(&board)
+            .run(&[a.clone(), b.clone()])
+            .unwrap();
+        assert!(result.exhaustive);
+        assert_eq!(result.boards, 1);
+        assert_eq!(result.players[0].equity, 1.0);
+        assert_eq!(result.players[1].win, 0.0);
+
+        // Терн: у A сет тузов, B нужно каре (Kc) или бродвей (десятки, Tc мертвая).
+        // Бордов 52 - 8 на руках - 4 на борде - 1 мертвая = 39.
+        let result = EquityCalc::new(GameVariant::Plo4)
+            .board(&board[..4])
+            .dead_cards(&cards("Tc"))
+            .run(&[a, b])
+            .unwrap();
+        assert!(result.exhaustive);
+        assert_eq!(result.boards, 39);
+        assert!((result.players[1].equity - 4.0 / 39.0).abs() < 1e-9);
+        assert!((result.players[0].win - 35.0 / 39.0).abs() < 1e-9);
+        assert_eq!(result.players[0].tie, 0.0);
+    }
+    #[test]
+    fn monte_carlo_is_seeded_and_close_to_known_equity() {
+        // Холдем AA против KK ~ 82%.
+        let hands = [hand("Ah As"), hand("Kd Kc")];
+        let calc = EquityCalc::new(GameVariant::Holdem).seed(7);
+        let result = calc.run(&hands).unwrap();
+        assert!(!result.exhaustive);
+        assert_eq!(result.boards, 20_000);
+        assert!((result.players[0].equity - 0.82).abs() < 0.02);
+        assert_eq!(result, calc.run(&hands).unwrap());
+
+        assert!(calc.run(&hands[..1]).is_err());
+        assert!(calc.run(&[hand("Ah As"), hand("Ah Kc")]).is_err());
+        let plo = EquityCalc::new(GameVariant::Plo4);
+        assert!(plo.run(&hands).is_err());
+    }
+    #[test]
+    fn hi_lo_splits_each_board() {
+        // Натсовый лоу против натсового хая на ривере: каждому по половине.
+        let low = hand("As 2h Kd Qc");
+        let high = hand("Kh Ks 9d 9c");
+        let board = cards("Kc 8h 7d 3s 4c");
+        let result = EquityCalc::new(GameVariant::Plo4HiLo)
+            .board(&board)
+            .run(&[low, high])
+            .unwrap();
+        assert_eq!(result.players[0].equity, 0.5);
+        assert_eq!(result.players[1].equity, 0.5);
+        assert_eq!(result.players[1].tie, 1.0);
+    }
+    #[test]
+    fn range_against_range_with_board_blocking() {
+        // Без стритов и флешей: у AA** всегда сет тузов, у QQ** максимум две пары.
+        let board = cards("Ad Kh 7c 2s 9d");
+        let aces = Range::parse("AA**").unwrap();
+        let queens = Range::parse("QQ**").unwrap();
+        let calc = EquityCalc::new(GameVariant::Plo4)
+            .board(&board)
+            .samples(500)
+            .seed(3);
+        let result = calc.run_ranges(&[aces.clone(), queens.clone()]).unwrap();
+        assert_eq!(result.boards, 500);
+        assert_eq!(result.players[0].equity, 1.0);
+        assert_eq!(result, calc.run_ranges(&[aces, queens.clone()]).unwrap());
+
+        // Префлоп AA** против QQ** примерно 68%.
+        let result = EquityCalc::new(GameVariant::Plo4)
+            .samples(4_000)
+            .run_ranges(&[Range::parse("AA**").unwrap(), queens.clone()])
+            .unwrap();
+        assert!((result.players[0].equity - 0.68).abs() < 0.05);
+
+        // На борде три короля - комбо KK** не осталось.
+        let kings = Range::parse("KK**").unwrap();
+        let calc = EquityCalc::new(GameVariant::Plo4).board(&cards("Kh Kd Kc"));
+        assert!(calc.run_ranges(&[kings, queens.clone()]).is_err());
+        assert!(EquityCalc::new(GameVariant::Plo5)
+            .run_ranges(&[queens.clone(), queens])
+            .is_err());
+    }
+}
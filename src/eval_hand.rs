@@ -1,10 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     collections::{HashMap, HashSet},
     fmt::Debug,
 };
 
-use crate::{Card, Hand, Rank, MAP_INLINE_REALCOMB};
+use lazy_static::lazy_static;
+
+use crate::{Card, GameVariant, Hand, Rank, Suit};
 
 #[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum ReadyHand {
@@ -35,74 +38,7 @@ pub enum ReadyHand {
     StreetFlash(Rank),
     FlashRoal,
 }
-impl ReadyHand {
-    // Must eq to Display and Debug
-    pub fn from_str(s: &str) -> Option<Self> {
-        if s.contains("FlashRoal") {
-            Some(ReadyHand::FlashRoal)
-        } else if s.contains("Streetflash") {
-            let temp = s.replace("Streetflash:", "");
-            let temp = temp.trim();
-            let rank = Rank::from_str(&temp).expect("wrong Rank street flash");
-            Some(ReadyHand::StreetFlash(rank))
-        } else if s.contains("Care") {
-            let temp = s.replace("Care:", "");
-            let temp = temp.trim();
-            let rank = Rank::from_str(&temp).expect("wrong Rank care");
-            Some(ReadyHand::Care(rank))
-        } else if s.contains("Full") {
-            let temp = s.replace("Full<3,2>:", "");
-            let ranks = Rank::from_str_to_vec(&temp);
-            Some(ReadyHand::FullHouse {
-                trips: ranks[0],
-                pair: ranks[1],
-            })
-        } else if s.contains("Flash") {
-            let temp = s.replace("Flash:", "");
-            let ranks = Rank::from_str_to_vec(&temp);
-            Some(Self::Flash(
-                ranks[0], ranks[1], ranks[2], ranks[3], ranks[4],
-            ))
-        } else if s.contains("Street") {
-            let temp = s.replace("Street:", "");
-            let ranks = Rank::from_str_to_vec(&temp);
-            Some(Self::Street(ranks[0]))
-        } else if s.contains("Trips") {
-            let temp = s.replace("Trips<trips,tk,lk>:", "");
-            let ranks = Rank::from_str_to_vec(&temp);
-            Some(Self::Trips {
-                trips: ranks[0],
-                top_kicker: ranks[1],
-                low_kicker: ranks[2],
-            })
-        } else if s.contains("TwoPair") {
-            let temp = s.replace("TwoPair<top,bot,k>:", "");
-            let ranks = Rank::from_str_to_vec(&temp);
-            Some(Self::TwoPair {
-                top: ranks[0],
-                bottom: ranks[1],
-                kicker: ranks[2],
-            })
-        } else if s.contains("Pair<pair,tk,mk,lk>:") {
-            let temp = s.replace("Pair<pair,tk,mk,lk>:", "");
-            let ranks = Rank::from_str_to_vec(&temp);
-            Some(Self::OnePair {
-                pair: ranks[0],
-                top_kicker: ranks[1],
-                mid_kicker: ranks[2],
-                low_kicker: ranks[3],
-            })
-        } else if s.contains("HightCards") {
-            let temp = s.replace("HightCards:", "");
-            let ranks = Rank::from_str_to_vec(&temp);
-            Some(Self::HightCards(
-                ranks[0], ranks[1], ranks[2], ranks[3], ranks[4],
-            ))
-        } else {
-            None
-        }
-    }
-}
+// Разбирается обратно через FromStr (hand::parse).
 impl Debug for ReadyHand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let present = match self {
@@ -143,51 +79,333 @@ impl Debug for ReadyHand {
         write!(f, "{}", present)
     }
 }
-pub fn real_comb(hand: &Hand, board: &Vec<Card>) -> ReadyHand {
-    // Get all variants of five cards.
-    let mut pair_cards_from_hand = vec![];
+/*
+Сила пятерки числом: один из 7462 классов эквивалентности, 1 - худшая (7-5-4-3-2), 7462 - роял.
+Больше значит сильнее, как у ReadyHand. В отличие от ReadyHand различает кикер у каре, поэтому
+ReadyHand::Care(r) переводится в сильнейший класс каре r.
+Собрать можно только существующий класс (new, из файла тоже через new), поэтому
+ReadyHand::from не паникует.
+*/
+#[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct HandStrength(u16);
+impl HandStrength {
+    pub const CLASSES: u16 = 7462;
+    pub fn new(value: u16) -> Result<Self, String> {
+        if (1..=Self::CLASSES).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(format!(
+                "Error: hand strength must be from 1 to {}, got {}",
+                Self::CLASSES,
+                value
+            ))
+        }
+    }
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+}
+impl TryFrom<u16> for HandStrength {
+    type Error = String;
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+impl From<HandStrength> for u16 {
+    fn from(strength: HandStrength) -> Self {
+        strength.0
+    }
+}
+impl From<HandStrength> for ReadyHand {
+    fn from(strength: HandStrength) -> Self {
+        STRENGTH_TABLES.ready_hands[strength.0 as usize - 1]
+    }
+}
+impl From<ReadyHand> for HandStrength {
+    fn from(ready_hand: ReadyHand) -> Self {
+        *STRENGTH_TABLES
+            .by_ready_hand
+            .get(&ready_hand)
+            .unwrap_or_else(|| unreachable!())
+    }
+}
+pub fn real_comb(hand: &Hand, board: &[Card]) -> ReadyHand {
+    ReadyHand::from(real_strength(hand, board))
+}
+pub fn real_comb_holdem(hand: &Hand, board: &[Card]) -> ReadyHand {
+    ReadyHand::from(real_strength_holdem(hand, board))
+}
+pub fn showdown_comb(variant: GameVariant, hand: &Hand, board: &[Card]) -> ReadyHand {
+    ReadyHand::from(showdown_strength(variant, hand, board))
+}
+pub fn real_strength(hand: &Hand, board: &[Card]) -> HandStrength {
+    /* Логика:
+    - Омаха: ровно 2 карты из руки и 3 с борда, из всех пятерок лучшая.
+    - Пятерки собираются на стеке без аллокаций и оцениваются через hand_strength().
+     */
+    let mut best = None;
     for i in 0..hand.cards.len() {
         for j in i + 1..hand.cards.len() {
-            let v = vec![hand.cards[i], hand.cards[j]];
-            pair_cards_from_hand.push(v);
+            for a in 0..board.len() {
+                for b in a + 1..board.len() {
+                    for c in b + 1..board.len() {
+                        let five_cards =
+                            [hand.cards[i], hand.cards[j], board[a], board[b], board[c]];
+                        best = best.max(Some(hand_strength(&five_cards)));
+                    }
+                }
+            }
         }
     }
-    // println!("{:?}->{:?}", hand, pair_cards_from_hand);
-    let mut trips_cards_from_board = vec![];
-    for i in 0..board.len() {
-        for j in i + 1..board.len() {
-            for k in j + 1..board.len() {
-                let v = vec![board[i], board[j], board[k]];
-                trips_cards_from_board.push(v);
+    best.unwrap_or_else(|| unreachable!())
+}
+pub fn real_strength_holdem(hand: &Hand, board: &[Card]) -> HandStrength {
+    /* Логика:
+    - Холдем: лучшие 5 карт из руки и борда в любом сочетании, в том числе борд целиком.
+    - Перебор всех пятерок из 7 карт (21 вариант) через hand_strength().
+     */
+    let mut all_cards = hand.cards.clone();
+    all_cards.extend_from_slice(board);
+    let n = all_cards.len();
+    let mut best = None;
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        let five_cards = [
+                            all_cards[a],
+                            all_cards[b],
+                            all_cards[c],
+                            all_cards[d],
+                            all_cards[e],
+                        ];
+                        best = best.max(Some(hand_strength(&five_cards)));
+                    }
+                }
             }
         }
     }
-    // println!("{:?}->{:?}", board, trips_cards_from_board);
-    let mut all_five_cards = vec![];
-    for pair in pair_cards_from_hand.iter() {
-        for trips in trips_cards_from_board.iter() {
-            let mut v = pair.clone();
-            v.extend_from_slice(&trips);
-            all_five_cards.push(v);
-        }
+    best.unwrap_or_else(|| unreachable!())
+}
+pub fn showdown_strength(variant: GameVariant, hand: &Hand, board: &[Card]) -> HandStrength {
+    if variant.is_omaha() {
+        real_strength(hand, board)
+    } else {
+        real_strength_holdem(hand, board)
     }
-    //println!("{:?}", all_five_cards);
-    let mut all_ready_hands = vec![];
-    for five_cards in all_five_cards.iter_mut() {
-        // all_ready_hands.push(combination(five_cards));
+}
+/*
+Таблицы для hand_strength. Класс пятерки зависит только от рангов и от того, флеш это или нет:
+- 5 разных рангов: индекс это битовая маска рангов (13 бит), отдельно для флешей и не флешей.
+- Есть повторы рангов (флеша быть не может): ключ это произведение простых чисел рангов.
+Классы упорядочены по combination() (плюс кикер каре), поэтому порядок тот же, что у ReadyHand.
+Таблицы строятся один раз в памяти, без файлов.
+*/
+const RANK_PRIMES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+const ALL_RANKS: [Rank; 13] = [
+    Rank::Two,
+    Rank::Three,
+    Rank::Four,
+    Rank::Five,
+    Rank::Six,
+    Rank::Seven,
+    Rank::Eight,
+    Rank::Nine,
+    Rank::Ten,
+    Rank::Jack,
+    Rank::Queen,
+    Rank::King,
+    Rank::Ace,
+];
+const ALL_SUITS: [Suit; 4] = [Suit::Spades, Suit::Harts, Suit::Daemonds, Suit::Clubs];
+struct StrengthTables {
+    flush: Vec<u16>, // 0 - нет такой маски.
+    no_flush: Vec<u16>,
+    by_primes: HashMap<u32, u16>,
+    ready_hands: Vec<ReadyHand>, // Индекс - сила минус 1.
+    by_ready_hand: HashMap<ReadyHand, HandStrength>,
+}
+lazy_static! {
+    static ref STRENGTH_TABLES: StrengthTables = StrengthTables::new();
+}
+impl StrengthTables {
+    fn new() -> Self {
+        // Все представители классов: (маска, флеш) или произведение простых.
+        let mut by_mask = vec![];
+        for mask in 0usize..1 << 13 {
+            if mask.count_ones() != 5 {
+                continue;
+            }
+            let suited = (0..13)
+                .rev()
+                .filter(|i| mask & (1 << i) != 0)
+                .map(|i| Card::new(ALL_RANKS[i], Suit::Spades))
+                .collect::<Vec<Card>>();
+            let mut offsuited = suited.clone();
+            offsuited[1].suit = Suit::Harts;
+            by_mask.push((mask, true, suited));
+            by_mask.push((mask, false, offsuited));
+        }
+        let mut by_primes = vec![];
+        for a in (0..13).rev() {
+            for b in (0..=a).rev() {
+                for c in (0..=b).rev() {
+                    for d in (0..=c).rev() {
+                        for e in (0..=d).rev() {
+                            let idx = [a, b, c, d, e];
+                            let mut seen = [0usize; 13];
+                            let mut cards = Vec::with_capacity(5);
+                            for &i in idx.iter() {
+                                if seen[i] == 4 {
+                                    break;
+                                }
+                                // Каждый повтор ранга берет следующую масть, так карты не совпадают.
+                                cards.push(Card::new(ALL_RANKS[i], ALL_SUITS[seen[i]]));
+                                seen[i] += 1;
+                            }
+                            if cards.len() < 5 || seen.iter().all(|&n| n < 2) {
+                                continue;
+                            }
+                            let key = idx.iter().map(|&i| RANK_PRIMES[i]).product::<u32>();
+                            by_primes.push((key, cards));
+                        }
+                    }
+                }
+            }
+        }
+        // Класс = ReadyHand + кикер каре (в ReadyHand его нет).
+        let class = |cards: &Vec<Card>| {
+            let ready_hand = combination(cards);
+            let kicker = match ready_hand {
+                ReadyHand::Care(r) => cards.iter().map(|c| c.rank).find(|&k| k != r),
+                _ => None,
+            };
+            (ready_hand, kicker)
+        };
+        let mut classes = by_mask
+            .iter()
+            .map(|(_, _, cards)| class(cards))
+            .chain(by_primes.iter().map(|(_, cards)| class(cards)))
+            .collect::<Vec<(ReadyHand, Option<Rank>)>>();
+        classes.sort_unstable();
+        classes.dedup();
+        let strength_of = |cards: &Vec<Card>| {
+            classes
+                .binary_search(&class(cards))
+                .unwrap_or_else(|_| unreachable!()) as u16
+                + 1
+        };
 
-        five_cards.sort_unstable_by(|a, b| b.cmp(a));
-        let mut key = five_cards
+        let mut flush = vec![0; 1 << 13];
+        let mut no_flush = vec![0; 1 << 13];
+        for (mask, is_flush, cards) in by_mask.iter() {
+            let table = if *is_flush { &mut flush } else { &mut no_flush };
+            table[*mask] = strength_of(cards);
+        }
+        let by_primes = by_primes
             .iter()
-            .map(|i| format!("{},", i))
-            .collect::<String>();
-        key.pop();
-        let comb = *MAP_INLINE_REALCOMB.get(&key).unwrap();
-        all_ready_hands.push(comb);
+            .map(|(key, cards)| (*key, strength_of(cards)))
+            .collect::<HashMap<u32, u16>>();
+        let ready_hands = classes.iter().map(|(r, _)| *r).collect::<Vec<ReadyHand>>();
+        let mut by_ready_hand = HashMap::new();
+        for (i, &ready_hand) in ready_hands.iter().enumerate() {
+            // Идем от слабых к сильным, у каре остается сильнейший кикер.
+            by_ready_hand.insert(ready_hand, HandStrength(i as u16 + 1));
+        }
+        Self {
+            flush,
+            no_flush,
+            by_primes,
+            ready_hands,
+            by_ready_hand,
+        }
+    }
+}
+pub fn hand_strength(five_cards: &[Card]) -> HandStrength {
+    // Класс пятерки по готовым таблицам. Порядок карт не важен.
+    let mut mask = 0usize;
+    let mut key = 1u32;
+    for card in five_cards.iter() {
+        mask |= 1 << card.rank as usize;
+        key *= RANK_PRIMES[card.rank as usize];
+    }
+    let tables = &*STRENGTH_TABLES;
+    let strength = if mask.count_ones() == 5 {
+        let is_flush = five_cards
+            .iter()
+            .all(|card| card.suit == five_cards[0].suit);
+        let table = if is_flush {
+            &tables.flush
+        } else {
+            &tables.no_flush
+        };
+        table[mask]
+    } else {
+        *tables.by_primes.get(&key).unwrap_or_else(|| unreachable!())
+    };
+    HandStrength(strength)
+}
+pub fn fast_comb(five_cards: &[Card]) -> ReadyHand {
+    // То же, что combination(), но по готовым таблицам.
+    ReadyHand::from(hand_strength(five_cards))
+}
+// Лоу 8-or-better: пять разных рангов не старше восьмерки, туз это 1. Ранги по убыванию.
+// Сравнение перевернуто, чтобы как у ReadyHand большее значение было сильнее: 5-4-3-2-A лучший.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct LowHand(pub [u8; 5]);
+impl Ord for LowHand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp(&self.0)
+    }
+}
+impl PartialOrd for LowHand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
-    all_ready_hands.sort_unstable();
-
-    *all_ready_hands.last().unwrap_or_else(|| unreachable!())
+}
+pub fn low_comb(five_cards: &[Card]) -> Option<LowHand> {
+    let mut ranks = [0u8; 5];
+    for (i, card) in five_cards.iter().enumerate() {
+        ranks[i] = match card.rank {
+            Rank::Ace => 1,
+            Rank::Two => 2,
+            Rank::Three => 3,
+            Rank::Four => 4,
+            Rank::Five => 5,
+            Rank::Six => 6,
+            Rank::Seven => 7,
+            Rank::Eight => 8,
+            _ => return None,
+        };
+    }
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+    if ranks.windows(2).any(|w| w[0] == w[1]) {
+        return None;
+    }
+    Some(LowHand(ranks))
+}
+pub fn real_low(hand: &Hand, board: &[Card]) -> Option<LowHand> {
+    /* Логика:
+    - Как real_comb: ровно 2 карты из руки и 3 с борда, из всех вариантов лучший лоу.
+    - None, если ни в одном варианте нет пяти разных рангов до восьмерки.
+     */
+    let mut best = None;
+    for i in 0..hand.cards.len() {
+        for j in i + 1..hand.cards.len() {
+            for a in 0..board.len() {
+                for b in a + 1..board.len() {
+                    for c in b + 1..board.len() {
+                        let five_cards =
+                            [hand.cards[i], hand.cards[j], board[a], board[b], board[c]];
+                        best = best.max(low_comb(&five_cards));
+                    }
+                }
+            }
+        }
+    }
+    best
 }
 pub fn combination(five_cards: &Vec<Card>) -> ReadyHand {
     /* Логика:
@@ -1008,6 +1226,161 @@ mod test {
         assert!(false);
     }
     #[test]
+    fn fast_comb_same_as_combination() {
+        // 1287 флешей + 1287 без повторов рангов + 4888 с повторами = 7462 классов.
+        // В Care нет кикера, поэтому 156 каре схлопываются в 13: остается 7319 разных ReadyHand.
+        let tables = &*STRENGTH_TABLES;
+        assert_eq!(tables.by_primes.len(), 4888);
+        assert_eq!(tables.ready_hands.len(), HandStrength::CLASSES as usize);
+        assert_eq!(tables.by_ready_hand.len(), 7319);
+        assert!(tables.ready_hands.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut rng = crate::seeded_rng(11);
+        for _ in 0..20_000 {
+            let mut five_cards = Hand::rnd_hand_of_size_with(5, &vec![], &mut rng).cards;
+            assert_eq!(fast_comb(&five_cards), combination(&five_cards));
+            five_cards.reverse();
+            assert_eq!(fast_comb(&five_cards), combination(&five_cards));
+        }
+    }
+    #[test]
+    fn strength_classes_and_conversions() {
+        let card = |s: &str| Card::from_string_ui(s.to_string());
+        let worst = ["7c", "5d", "4h", "3s", "2c"].map(card);
+        assert_eq!(hand_strength(&worst), HandStrength::new(1).unwrap());
+        let royal = ["As", "Ks", "Qs", "Js", "Ts"].map(card);
+        assert_eq!(hand_strength(&royal).value(), HandStrength::CLASSES);
+        assert!(HandStrength::new(0).is_err());
+        assert!(HandStrength::new(HandStrength::CLASSES + 1).is_err());
+        assert!(serde_json::from_str::<HandStrength>("0").is_err());
+        assert_eq!(serde_json::to_string(&hand_strength(&worst)).unwrap(), "1");
+        for i in 1..=HandStrength::CLASSES {
+            let strength = HandStrength::new(i).unwrap();
+            let ready_hand = ReadyHand::from(strength);
+            let back = HandStrength::from(ready_hand);
+            match ready_hand {
+                ReadyHand::Care(_) => assert!(back >= strength),
+                _ => assert_eq!(back, strength),
+            }
+        }
+        // Холдем, каре на борде: ReadyHand считает руки равными, сила различает кикер.
+        let board = ["Kc", "Kd", "Ks", "Kh", "5c"].map(card);
+        let hand_a = Hand::from_cards(vec![card("Ac"), card("2d")]).unwrap();
+        let hand_q = Hand::from_cards(vec![card("Qc"), card("2h")]).unwrap();
+        assert_eq!(
+            real_comb_holdem(&hand_a, &board),
+            real_comb_holdem(&hand_q, &board)
+        );
+        assert!(real_strength_holdem(&hand_a, &board) > real_strength_holdem(&hand_q, &board));
+
+        // Фейковые классификаторы принимают и силу числом.
+        let hand = Hand::new(card("As"), card("Ks"), card("2d"), card("3c")).unwrap();
+        let board = vec![card("Qs"), card("Js"), card("7s"), card("4h"), card("2h")];
+        let strength = real_strength(&hand, &board);
+        assert_eq!(
+            crate::postflop_game::eval_fake_hand::fake_comb_side_ready(&hand, strength, &board),
+            crate::postflop_game::eval_fake_hand::fake_comb_side_ready(
+                &hand,
+                real_comb(&hand, &board),
+                &board
+            )
+        );
+    }
+    #[test]
+    #[ignore = "It's a speed test"]
+    fn fast_comb_speed() {
+        // cargo test --release fast_comb_speed -- --ignored --nocapture
+        let mut rng = crate::seeded_rng(3);
+        let deals = (0..100_000)
+            .map(|_| {
+                let hand = Hand::rnd_hand_with(&vec![], &mut rng);
+                let board = Hand::rnd_hand_of_size_with(5, &hand.cards, &mut rng).cards;
+                (hand, board)
+            })
+            .collect::<Vec<(Hand, Vec<Card>)>>();
+        let time = std::time::Instant::now();
+        deals.iter().for_each(|(hand, board)| {
+            real_comb(hand, board);
+        });
+        println!("real_comb: {:?} per call", time.elapsed() / 100_000);
+        let time = std::time::Instant::now();
+        deals.iter().for_each(|(_, board)| {
+            combination(board);
+        });
+        println!("combination: {:?} per call", time.elapsed() / 100_000);
+        let time = std::time::Instant::now();
+        deals.iter().for_each(|(_, board)| {
+            fast_comb(board);
+        });
+        println!("fast_comb: {:?} per call", time.elapsed() / 100_000);
+    }
+    #[test]
+    fn holdem_best_five_of_seven() {
+        let board = vec![
+            Card::new(Rank::Ace, Suit::Clubs),
+            Card::new(Rank::King, Suit::Clubs),
+            Card::new(Rank::Seven, Suit::Clubs),
+            Card::new(Rank::Four, Suit::Clubs),
+            Card::new(Rank::Two, Suit::Spades),
+        ];
+        // Одна карта из руки: в омахе флеша нет, в холдеме есть.
+        let hand = Hand::from_cards(vec![
+            Card::new(Rank::Nine, Suit::Clubs),
+            Card::new(Rank::Three, Suit::Daemonds),
+        ])
+        .unwrap();
+        assert!(matches!(
+            real_comb_holdem(&hand, &board),
+            ReadyHand::Flash(..)
+        ));
+        // Борд и одна карта из руки: старшие карты.
+        let board = vec![
+            Card::new(Rank::Ace, Suit::Clubs),
+            Card::new(Rank::King, Suit::Clubs),
+            Card::new(Rank::Seven, Suit::Clubs),
+            Card::new(Rank::Four, Suit::Harts),
+            Card::new(Rank::Two, Suit::Spades),
+        ];
+        let hand = Hand::from_cards(vec![
+            Card::new(Rank::Six, Suit::Daemonds),
+            Card::new(Rank::Three, Suit::Daemonds),
+        ])
+        .unwrap();
+        let board_plays = combination(&vec![
+            Card::new(Rank::Ace, Suit::Clubs),
+            Card::new(Rank::King, Suit::Clubs),
+            Card::new(Rank::Seven, Suit::Clubs),
+            Card::new(Rank::Six, Suit::Daemonds),
+            Card::new(Rank::Four, Suit::Harts),
+        ]);
+        assert_eq!(real_comb_holdem(&hand, &board), board_plays);
+        assert_eq!(
+            showdown_comb(GameVariant::Holdem, &hand, &board),
+            board_plays
+        );
+    }
+    #[test]
+    fn low_eight_or_better_two_from_hand() {
+        let card = |s: &str| Card::from_string_ui(s.to_string());
+        let board = ["Ac", "2d", "7h", "Kc", "Qs"].map(card).to_vec();
+        // 3 и 4 из руки + A 2 7 с борда.
+        let hand = Hand::new(card("3s"), card("4s"), card("Kh"), card("Kd")).unwrap();
+        assert_eq!(real_low(&hand, &board), Some(LowHand([7, 4, 3, 2, 1])));
+        // Одна низкая карта в руке - лоу нет, хотя в холдеме был бы.
+        let hand = Hand::new(card("3s"), card("Js"), card("Kh"), card("Kd")).unwrap();
+        assert_eq!(real_low(&hand, &board), None);
+        // Туз в руке совпал с тузом на борде - пара не считается, берется другая пара карт.
+        let hand = Hand::new(card("Ah"), card("2s"), card("5h"), card("6d")).unwrap();
+        assert_eq!(real_low(&hand, &board), Some(LowHand([7, 6, 5, 2, 1])));
+        // Меньше трех низких на борде - лоу нет ни у кого.
+        let board = ["Ac", "2d", "9h", "Kc", "Qs"].map(card).to_vec();
+        let hand = Hand::new(card("3s"), card("4s"), card("5h"), card("6d")).unwrap();
+        assert_eq!(real_low(&hand, &board), None);
+
+        assert!(LowHand([5, 4, 3, 2, 1]) > LowHand([6, 4, 3, 2, 1]));
+        assert!(LowHand([8, 5, 4, 3, 2]) > LowHand([8, 6, 3, 2, 1]));
+    }
+    #[test]
     fn cmp_diff_group_diff_comboes() {
         // (Js Jh Tc 7h) on [Ac, Kc, Qc, Jc, 9c] is Street: A сравнение стрита и сета
         let hand = Hand::new(
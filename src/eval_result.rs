@@ -1,4 +1,4 @@
-use crate::{Position, ReadyHand};
+use crate::{eval_hand::LowHand, Game, Position};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::collections::{BTreeMap, HashMap};
@@ -7,6 +7,7 @@ use std::collections::{BTreeMap, HashMap};
 Данные:
 1. Вектор для суммаризации вложений в пот на всех улицах.
 2. Мапа для хранения соответствия позиция - готовая рука. (все кроме сфолженных позиций)
+    Рука это любое сравнимое значение: ReadyHand или числовая сила HandStrength.
 3. Отсортированная мапа
     - Во-первых автоматическая сортировка за счет BTreeMap, от слабой комбы к сильной.
     - Во-вторых каждая строчка мапы содержит вектор позиций, которая содержит эту комбинацию.
@@ -15,9 +16,9 @@ use std::collections::{BTreeMap, HashMap};
 Результат:
     Мапа содержит только те позиции, которые борются за пот, все кроме сфолдивших к риверу.
  */
-pub fn eval_clear_win_loose(
+pub fn eval_clear_win_loose<H: Ord + Copy>(
     all_positions_and_money: Vec<HashMap<Position, Decimal>>,
-    real_hands_end: &HashMap<Position, ReadyHand>,
+    real_hands_end: &HashMap<Position, H>,
     extra_money: Option<Decimal>,
 ) -> HashMap<Position, Decimal> {
     let take_back_from_pot =
@@ -63,9 +64,134 @@ pub fn eval_clear_win_loose_five_fold(
     });
     win_loose
 }
-fn eval_take_back_from_pot(
+pub fn eval_side_pots_win_loose<H: Ord>(
+    game: &impl Game,
+    real_hands_end: &HashMap<Position, H>,
+) -> HashMap<Position, Decimal> {
+    /* Логика:
+    - Каждый сайд-пот из game.main_pot().side_pots делится между лучшими руками из его претендентов.
+    - Если у претендентов банка нет рук (все сфолдили, кроме одного), то банк забирает оставшийся претендент.
+    - Результат: выигрыш минус полный вклад позиции за всю раздачу.
+    */
+    let mut take_back_map: HashMap<Position, Decimal> = HashMap::new();
+    for side_pot in game.main_pot().side_pots.iter() {
+        let best = side_pot
+            .eligible
+            .iter()
+            .filter_map(|pos| real_hands_end.get(pos))
+            .max();
+        let winners = match best {
+            Some(best) => side_pot
+                .eligible
+                .iter()
+                .filter(|&pos| real_hands_end.get(pos) == Some(best))
+                .copied()
+                .collect::<Vec<Position>>(),
+            None => side_pot.eligible.clone(),
+        };
+        if winners.is_empty() {
+            continue;
+        }
+        let share = side_pot.value / Decimal::from(winners.len());
+        for pos in winners {
+            *take_back_map.entry(pos).or_insert(dec!(0)) += share;
+        }
+    }
+    let mut win_loose = HashMap::new();
+    game.total_commits().iter().for_each(|(&pos, &money)| {
+        let win_money = *take_back_map.get(&pos).unwrap_or(&Decimal::ZERO);
+        win_loose.insert(pos, win_money - money);
+    });
+    win_loose
+}
+pub fn eval_side_pots_hi_lo_win_loose<H: Ord>(
+    game: &impl Game,
+    real_hands_end: &HashMap<Position, H>,
+    low_hands_end: &HashMap<Position, LowHand>,
+) -> HashMap<Position, Decimal> {
+    /* Логика:
+    - Каждый сайд-пот делится пополам: хай половина лучшим ReadyHand, лоу половина лучшим LowHand
+      среди претендентов этого банка. Если лоу ни у кого из претендентов нет, весь банк хаю.
+    - Поровну внутри половины, отсюда и квартеринг: 2 одинаковых лоу получают по четверти банка.
+    - Нечетная фишка (ODD_CHIP): при делении банка пополам лишняя идет хаю, при делении половины
+      между равными руками - первому слева от баттона (по порядку постфлопа).
+    - Результат: выигрыш минус полный вклад позиции за всю раздачу.
+    */
+    let postflop_order = game.table().postflop_order();
+    let mut take_back_map: HashMap<Position, Decimal> = HashMap::new();
+    for side_pot in game.main_pot().side_pots.iter() {
+        let mut high_winners =
+            best_from_button(&side_pot.eligible, real_hands_end, &postflop_order);
+        if high_winners.is_empty() {
+            // Рук нет, все сфолдили кроме одного.
+            high_winners = side_pot.eligible.clone();
+        }
+        if high_winners.is_empty() {
+            continue;
+        }
+        let low_winners = best_from_button(&side_pot.eligible, low_hands_end, &postflop_order);
+        let high_value = if low_winners.is_empty() {
+            side_pot.value
+        } else {
+            side_pot.value - floor_chips(side_pot.value / dec!(2))
+        };
+        for (pos, share) in split_by_chips(high_value, &high_winners)
+            .into_iter()
+            .chain(split_by_chips(side_pot.value - high_value, &low_winners))
+        {
+            *take_back_map.entry(pos).or_insert(dec!(0)) += share;
+        }
+    }
+    let mut win_loose = HashMap::new();
+    game.total_commits().iter().for_each(|(&pos, &money)| {
+        let win_money = *take_back_map.get(&pos).unwrap_or(&Decimal::ZERO);
+        win_loose.insert(pos, win_money - money);
+    });
+    win_loose
+}
+fn best_from_button<T: Ord>(
+    eligible: &[Position],
+    hands: &HashMap<Position, T>,
+    postflop_order: &[Position],
+) -> Vec<Position> {
+    // Позиции с лучшей рукой среди eligible, по порядку от баттона. Пусто, если рук нет.
+    let best = eligible.iter().filter_map(|pos| hands.get(pos)).max();
+    let mut winners = eligible
+        .iter()
+        .filter(|&pos| best.is_some() && hands.get(pos) == best)
+        .copied()
+        .collect::<Vec<Position>>();
+    winners.sort_by_key(|pos| postflop_order.iter().position(|p| p == pos));
+    winners
+}
+// Самая мелкая фишка, дальше которой банк при дележе не дробится.
+const ODD_CHIP: Decimal = dec!(0.01);
+fn floor_chips(value: Decimal) -> Decimal {
+    (value / ODD_CHIP).floor() * ODD_CHIP
+}
+fn split_by_chips(value: Decimal, winners: &[Position]) -> Vec<(Position, Decimal)> {
+    // winners уже в порядке от баттона, нечетные фишки раздаются по одной с начала.
+    if winners.is_empty() {
+        return vec![];
+    }
+    let count = Decimal::from(winners.len());
+    let share = floor_chips(value / count);
+    let mut odd_chips = value - share * count;
+    winners
+        .iter()
+        .map(|&pos| {
+            let mut money = share;
+            if odd_chips > Decimal::ZERO {
+                money += ODD_CHIP;
+                odd_chips -= ODD_CHIP;
+            }
+            (pos, money)
+        })
+        .collect()
+}
+fn eval_take_back_from_pot<H: Ord + Copy>(
     all_positions_and_money: &Vec<HashMap<Position, Decimal>>,
-    real_hands_end: &HashMap<Position, ReadyHand>,
+    real_hands_end: &HashMap<Position, H>,
     extra_money: Option<Decimal>,
 ) -> HashMap<Position, Decimal> {
     let mut take_back_map = HashMap::new();
@@ -125,10 +251,10 @@ fn summarize_position_money(
         });
     summ_pos_money
 }
-fn winners_map(
+fn winners_map<H: Ord + Copy>(
     summ_pos_money: &HashMap<Position, Decimal>,
-    real_hands_end: &HashMap<Position, ReadyHand>,
-) -> BTreeMap<ReadyHand, Vec<Position>> {
+    real_hands_end: &HashMap<Position, H>,
+) -> BTreeMap<H, Vec<Position>> {
     // Get double sorted spacial hash map.
     let mut double_sorted_map = BTreeMap::new();
     real_hands_end.iter().for_each(|(&pos, &ready_comb)| {
@@ -196,7 +322,8 @@ fn decrease_rake(
 #[cfg(test)]
 mod eval_result {
     use super::*;
-    use crate::Rank;
+    use crate::test_utils::preflop_with_short_allins;
+    use crate::{Rank, ReadyHand};
     #[test]
     #[ignore = "Without rake"]
     fn hard_one() {
@@ -436,6 +563,58 @@ mod eval_result {
         assert_eq!(real_wins, suppose_wins);
     }
     #[test]
+    fn side_pots_short_allin_wins_only_main() {
+        let game = preflop_with_short_allins();
+        let real_hands_end = HashMap::from([
+            (Position::Utg, ReadyHand::FlashRoal),
+            (Position::Mp, ReadyHand::Street(Rank::Ace)),
+            (Position::Co, ReadyHand::Street(Rank::King)),
+        ]);
+        let real_wins = eval_side_pots_win_loose(&game, &real_hands_end);
+        let suppose_wins = HashMap::from([
+            (Position::Sb, dec!(-0.5)),
+            (Position::Bb, dec!(-1)),
+            (Position::Utg, dec!(21.5)),
+            (Position::Mp, dec!(30)),
+            (Position::Co, dec!(-50)),
+            (Position::Btn, dec!(0)),
+        ]);
+        assert_eq!(real_wins, suppose_wins);
+    }
+    #[test]
+    fn hi_lo_quartering_and_odd_chip() {
+        let game = preflop_with_short_allins();
+        let real_hands_end = HashMap::from([
+            (Position::Utg, ReadyHand::FlashRoal),
+            (Position::Mp, ReadyHand::Street(Rank::Ace)),
+            (Position::Co, ReadyHand::Street(Rank::King)),
+        ]);
+        let low = LowHand([8, 6, 4, 3, 1]);
+        let low_hands_end = HashMap::from([(Position::Mp, low), (Position::Co, low)]);
+        let real_wins = eval_side_pots_hi_lo_win_loose(&game, &real_hands_end, &low_hands_end);
+        // Главный: хай Utg 15.75, лоу 15.75 пополам, нечетная фишка Mp (раньше Co от баттона).
+        // Сайд: хай Mp 40, лоу пополам по 20 - Mp забирает три четверти.
+        let suppose_wins = HashMap::from([
+            (Position::Sb, dec!(-0.5)),
+            (Position::Bb, dec!(-1)),
+            (Position::Utg, dec!(5.75)),
+            (Position::Mp, dec!(17.88)),
+            (Position::Co, dec!(-22.13)),
+            (Position::Btn, dec!(0)),
+        ]);
+        assert_eq!(real_wins, suppose_wins);
+
+        // Без лоу весь банк хаю, как в обычной омахе.
+        assert_eq!(
+            eval_side_pots_hi_lo_win_loose(&game, &real_hands_end, &HashMap::new()),
+            eval_side_pots_win_loose(&game, &real_hands_end)
+        );
+        assert_eq!(
+            split_by_chips(dec!(0.05), &[Position::Sb, Position::Bb]),
+            vec![(Position::Sb, dec!(0.03)), (Position::Bb, dec!(0.02))]
+        );
+    }
+    #[test]
     #[ignore = "Without rake"]
     fn easy_four_alternative() {
         let all_positions_and_money = vec![HashMap::from([
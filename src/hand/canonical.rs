@@ -0,0 +1,131 @@
+use super::real_abstract_cards::{Card, Hand, Rank, Suit};
+
+// Порядок, в котором масти получают новые имена.
+const CANONICAL_ORDER: [Suit; 4] = [Suit::Daemonds, Suit::Harts, Suit::Clubs, Suit::Spades];
+
+/*
+Перестановка мастей. Раздачи, которые отличаются только названием мастей (As Ks на червовом борде и
+Ad Kd на трефовом), после перестановки дают одинаковые карты.
+- canonical: у каждой масти подпись - ранги этой масти на борде, потом в каждой руке по порядку.
+  Масти сортируются по подписи и получают имена по CANONICAL_ORDER. Масти с одинаковой подписью
+  взаимозаменяемы, поэтому результат не зависит от исходных имен.
+- first_seen: имена по порядку первого появления масти в картах. Для ключей, где важны только масти.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuitMap([Suit; 4]); // Индекс - старая масть (as usize), значение - новая.
+impl SuitMap {
+    pub fn canonical(board: &[Card], hands: &[&Hand]) -> Self {
+        let signature = |suit: Suit| {
+            let mut groups = vec![suit_ranks(board, suit)];
+            groups.extend(hands.iter().map(|hand| suit_ranks(&hand.cards, suit)));
+            groups
+        };
+        let mut suits = CANONICAL_ORDER;
+        suits.sort_by_cached_key(|&suit| std::cmp::Reverse(signature(suit)));
+        Self::from_order(&suits)
+    }
+    pub fn first_seen(cards: &[Card]) -> Self {
+        let mut suits = Vec::with_capacity(4);
+        for card in cards {
+            if !suits.contains(&card.suit) {
+                suits.push(card.suit);
+            }
+        }
+        for suit in CANONICAL_ORDER {
+            if !suits.contains(&suit) {
+                suits.push(suit);
+            }
+        }
+        Self::from_order(&suits)
+    }
+    fn from_order(suits: &[Suit]) -> Self {
+        let mut map = CANONICAL_ORDER;
+        for (&old, new) in suits.iter().zip(CANONICAL_ORDER) {
+            map[old as usize] = new;
+        }
+        Self(map)
+    }
+    pub fn suit(&self, suit: Suit) -> Suit {
+        self.0[suit as usize]
+    }
+    pub fn card(&self, card: Card) -> Card {
+        Card::new(card.rank, self.suit(card.suit))
+    }
+    // Порядок карт сохраняется.
+    pub fn cards(&self, cards: &[Card]) -> Vec<Card> {
+        cards.iter().map(|&card| self.card(card)).collect()
+    }
+    pub fn hand(&self, hand: &Hand) -> Hand {
+        Hand::from_cards(self.cards(&hand.cards)).unwrap_or_else(|_| unreachable!())
+    }
+}
+
+// Канонический вид руки и борда. Борд сортируется от старшей карты, как в PostflopGame.
+pub fn canonical(hand: &Hand, board: &[Card]) -> (Hand, Vec<Card>) {
+    let map = SuitMap::canonical(board, &[hand]);
+    let mut board = map.cards(board);
+    board.sort_unstable_by(|a, b| b.cmp(a));
+    (map.hand(hand), board)
+}
+
+fn suit_ranks(cards: &[Card], suit: Suit) -> Vec<Rank> {
+    let mut ranks = cards
+        .iter()
+        .filter(|card| card.suit == suit)
+        .map(|card| card.rank)
+        .collect::<Vec<_>>();
+    ranks.sort_unstable_by(|a, b| b.cmp(a));
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seeded_rng;
+    use crate::test_utils::{cards, hand};
+    use rand::seq::SliceRandom;
+
+    #[test]
+    fn isomorphic_spots_are_equal() {
+        let my_hand = hand("As Ks 7d 2c");
+        let board = cards("Qh Th 5h 3s 3d");
+        let other_hand = hand("Ad Kd 7s 2h");
+        let other_board = cards("Qc Tc 5c 3d 3s");
+        assert_eq!(
+            canonical(&my_hand, &board),
+            canonical(&other_hand, &other_board)
+        );
+
+        // Другая масть у короля - другая раздача.
+        let not_same = hand("As Kh 7d 2c");
+        assert_ne!(canonical(&my_hand, &board), canonical(&not_same, &board));
+
+        // Любая перестановка мастей дает тот же канонический вид.
+        let mut rng = seeded_rng(1);
+        for _ in 0..200 {
+            let hand = Hand::rnd_hand_with(&vec![], &mut rng);
+            let mut deck = Card::deck();
+            deck.retain(|card| !hand.cards.contains(card));
+            deck.shuffle(&mut rng);
+            let board = deck[..5].to_vec();
+            let mut order = CANONICAL_ORDER;
+            order.shuffle(&mut rng);
+            let relabel = SuitMap::from_order(&order);
+            assert_eq!(
+                canonical(&hand, &board),
+                canonical(&relabel.hand(&hand), &relabel.cards(&board))
+            );
+        }
+    }
+    #[test]
+    fn first_seen_relabels_in_order() {
+        let board = cards("Ks Qs Th 5s 2c");
+        let map = SuitMap::first_seen(&board);
+        let suits = map
+            .cards(&board)
+            .iter()
+            .map(|card| format!("{:?}", card.suit))
+            .collect::<String>();
+        assert_eq!(suits, "ddhdc");
+    }
+}
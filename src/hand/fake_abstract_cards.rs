@@ -62,13 +62,13 @@ impl Debug for FakeSuitKind {
 
 #[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Clone)]
 pub struct FakeHand {
-    pub cards: [FakeCard; 4],
+    pub cards: Vec<FakeCard>, // Столько же, сколько карт в руке.
     pub kind: FakeSuitKind,
     pub paired: Pairing,
 }
 impl FakeHand {
     pub fn from(hand: &Hand) -> Self {
-        let mut fake_cards: [FakeCard; 4] = hand
+        let mut fake_cards: Vec<FakeCard> = hand
             .cards
             .iter()
             .cloned()
@@ -83,9 +83,7 @@ impl FakeHand {
                     Rank::Four | Rank::Three | Rank::Two => FakeRank::GarbageCard,
                 },
             })
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap_or_else(|_| unreachable!());
+            .collect();
         fake_cards.sort_unstable_by(|a, b| b.cmp(a));
 
         let kind = if hand.is_off_suited() {
@@ -108,10 +106,9 @@ impl FakeHand {
 }
 impl Debug for FakeHand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{:?}{:?}{:?}{:?}{:?}{:?}",
-            self.cards[0], self.cards[1], self.cards[2], self.cards[3], self.kind, self.paired
-        )
+        self.cards
+            .iter()
+            .try_for_each(|card| write!(f, "{:?}", card))?;
+        write!(f, "{:?}{:?}", self.kind, self.paired)
     }
 }
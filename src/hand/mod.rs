@@ -1,5 +1,9 @@
+pub use canonical::{canonical, SuitMap};
 pub use fake_abstract_cards::{FakeCard, FakeHand, FakeRank, FakeSuitKind};
+pub use parse::{parse_cards, Board, ParseCardError, ParseReadyHandError};
 pub use real_abstract_cards::{Card, Hand, Pairing, Rank, Suit};
 
+pub mod canonical;
 pub mod fake_abstract_cards;
+pub mod parse;
 pub mod real_abstract_cards;
@@ -0,0 +1,318 @@
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use super::real_abstract_cards::{Card, Hand, Rank, Suit};
+use crate::ReadyHand;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCardError {
+    Empty,
+    WrongRank(String),
+    WrongSuit(String),
+    NoSuit(String), // Ранг без масти в конце строки.
+    WrongCount { expected: &'static str, got: usize },
+    DuplicateCard(Card),
+}
+impl Display for ParseCardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Error: empty cards string"),
+            Self::WrongRank(s) => write!(f, "Error: wrong rank '{}'", s),
+            Self::WrongSuit(s) => write!(f, "Error: wrong suit '{}'", s),
+            Self::NoSuit(s) => write!(f, "Error: no suit after rank '{}'", s),
+            Self::WrongCount { expected, got } => {
+                write!(f, "Error: need {} cards, got {}", expected, got)
+            }
+            Self::DuplicateCard(card) => write!(f, "Error: card {} is used twice", card),
+        }
+    }
+}
+impl std::error::Error for ParseCardError {}
+
+/*
+Разбор карт для ввода с распознавателя экрана:
+- ранг и масть в любом регистре ("as", "AS", "As"), десятка как "T" или "10";
+- между картами любые разделители из SEPARATORS или ничего ("AsKd", "As Kd", "As,kd", "As-10d").
+Display пишет ранг заглавной, масть строчной: "As", руку слитно "AsKdQhJc", борд через пробел.
+*/
+const SEPARATORS: [char; 5] = [',', ';', '-', '|', '/'];
+
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, ParseCardError> {
+    let chars = s
+        .chars()
+        .filter(|c| !c.is_whitespace() && !SEPARATORS.contains(c))
+        .collect::<Vec<char>>();
+    if chars.is_empty() {
+        return Err(ParseCardError::Empty);
+    }
+    let mut cards = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let (rank, rank_len) = match (chars[i], chars.get(i + 1)) {
+            ('1', Some('0')) => (Rank::Ten, 2),
+            (c, _) => (
+                parse_rank(c).ok_or_else(|| ParseCardError::WrongRank(c.to_string()))?,
+                1,
+            ),
+        };
+        i += rank_len;
+        let c = *chars
+            .get(i)
+            .ok_or_else(|| ParseCardError::NoSuit(chars[i - rank_len..].iter().collect()))?;
+        let suit = parse_suit(c).ok_or_else(|| ParseCardError::WrongSuit(c.to_string()))?;
+        i += 1;
+        cards.push(Card::new(rank, suit));
+    }
+    let mut uniq = HashSet::with_capacity(cards.len());
+    if let Some(card) = cards.iter().find(|&&card| !uniq.insert(card)) {
+        return Err(ParseCardError::DuplicateCard(*card));
+    }
+    Ok(cards)
+}
+fn parse_rank(c: char) -> Option<Rank> {
+    Rank::from_str(&c.to_ascii_uppercase().to_string())
+}
+fn parse_suit(c: char) -> Option<Suit> {
+    match c.to_ascii_lowercase() {
+        's' => Some(Suit::Spades),
+        'c' => Some(Suit::Clubs),
+        'h' => Some(Suit::Harts),
+        'd' => Some(Suit::Daemonds),
+        _ => None,
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = parse_cards(s)?;
+        match cards[..] {
+            [card] => Ok(card),
+            _ => Err(ParseCardError::WrongCount {
+                expected: "1",
+                got: cards.len(),
+            }),
+        }
+    }
+}
+
+impl FromStr for Hand {
+    type Err = ParseCardError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = parse_cards(s)?;
+        if !(2..=5).contains(&cards.len()) {
+            return Err(ParseCardError::WrongCount {
+                expected: "from 2 to 5",
+                got: cards.len(),
+            });
+        }
+        Ok(Hand::from_cards(cards).unwrap_or_else(|_| unreachable!()))
+    }
+}
+impl Display for Hand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.cards.iter().try_for_each(|card| write!(f, "{}", card))
+    }
+}
+
+// Борд флопа, терна или ривера. Порядок карт сохраняется как есть.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Board(pub Vec<Card>);
+impl FromStr for Board {
+    type Err = ParseCardError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cards = parse_cards(s)?;
+        if !(3..=5).contains(&cards.len()) {
+            return Err(ParseCardError::WrongCount {
+                expected: "from 3 to 5",
+                got: cards.len(),
+            });
+        }
+        Ok(Self(cards))
+    }
+}
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cards = self
+            .0
+            .iter()
+            .map(|card| card.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", cards.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseReadyHandError {
+    WrongKind(String),
+    WrongRank(String),
+    WrongCount { expected: usize, got: usize },
+}
+impl Display for ParseReadyHandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongKind(s) => write!(f, "Error: wrong ready hand kind '{}'", s),
+            Self::WrongRank(s) => write!(f, "Error: wrong rank '{}'", s),
+            Self::WrongCount { expected, got } => {
+                write!(f, "Error: need {} ranks, got {}", expected, got)
+            }
+        }
+    }
+}
+impl std::error::Error for ParseReadyHandError {}
+
+/*
+ReadyHand в формате Debug: "Full<3,2>: A K", "FlashRoal:". Вид до двоеточия должен совпасть
+целиком, после него ровно столько рангов, сколько у этого вида.
+*/
+impl FromStr for ReadyHand {
+    type Err = ParseReadyHandError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, ranks) = s
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| ParseReadyHandError::WrongKind(s.trim().to_string()))?;
+        let expected = match kind {
+            "FlashRoal" => 0,
+            "Streetflash" | "Care" | "Street" => 1,
+            "Full<3,2>" => 2,
+            "Trips<trips,tk,lk>" | "TwoPair<top,bot,k>" => 3,
+            "Pair<pair,tk,mk,lk>" => 4,
+            "Flash" | "HightCards" => 5,
+            _ => return Err(ParseReadyHandError::WrongKind(kind.to_string())),
+        };
+        let r = ranks
+            .split_whitespace()
+            .map(|r| Rank::from_str(r).ok_or_else(|| ParseReadyHandError::WrongRank(r.to_string())))
+            .collect::<Result<Vec<Rank>, _>>()?;
+        if r.len() != expected {
+            return Err(ParseReadyHandError::WrongCount {
+                expected,
+                got: r.len(),
+            });
+        }
+        Ok(match kind {
+            "FlashRoal" => ReadyHand::FlashRoal,
+            "Streetflash" => ReadyHand::StreetFlash(r[0]),
+            "Care" => ReadyHand::Care(r[0]),
+            "Street" => ReadyHand::Street(r[0]),
+            "Full<3,2>" => ReadyHand::FullHouse {
+                trips: r[0],
+                pair: r[1],
+            },
+            "Trips<trips,tk,lk>" => ReadyHand::Trips {
+                trips: r[0],
+                top_kicker: r[1],
+                low_kicker: r[2],
+            },
+            "TwoPair<top,bot,k>" => ReadyHand::TwoPair {
+                top: r[0],
+                bottom: r[1],
+                kicker: r[2],
+            },
+            "Pair<pair,tk,mk,lk>" => ReadyHand::OnePair {
+                pair: r[0],
+                top_kicker: r[1],
+                mid_kicker: r[2],
+                low_kicker: r[3],
+            },
+            "Flash" => ReadyHand::Flash(r[0], r[1], r[2], r[3], r[4]),
+            _ => ReadyHand::HightCards(r[0], r[1], r[2], r[3], r[4]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HandStrength;
+
+    #[test]
+    fn parse_cards_hands_and_boards() {
+        assert_eq!("As".parse(), Ok(Card::new(Rank::Ace, Suit::Spades)));
+        assert_eq!("10h".parse(), Ok(Card::new(Rank::Ten, Suit::Harts)));
+        assert_eq!(" tD ".parse(), Ok(Card::new(Rank::Ten, Suit::Daemonds)));
+
+        let hand = "AsKdQhJc".parse::<Hand>().unwrap();
+        for s in ["as kd qh jc", "Jc,Qh;KD-AS", "AS|KD/QH JC"] {
+            assert_eq!(s.parse::<Hand>(), Ok(hand.clone()), "{}", s);
+        }
+        assert_eq!(hand.to_string(), "AsKdQhJc");
+        assert_eq!(
+            "10s 9s 8d 7d".parse::<Hand>().unwrap().to_string(),
+            "Ts9s8d7d"
+        );
+
+        let board = "Ac Kc Ts".parse::<Board>().unwrap();
+        assert_eq!("ackc10s".parse::<Board>(), Ok(board.clone()));
+        assert_eq!(board.to_string(), "Ac Kc Ts");
+        assert_eq!(board.to_string().parse::<Board>(), Ok(board));
+    }
+    #[test]
+    fn parse_errors_without_panic() {
+        assert_eq!("".parse::<Card>(), Err(ParseCardError::Empty));
+        assert_eq!(
+            "Yd".parse::<Card>(),
+            Err(ParseCardError::WrongRank(String::from("Y")))
+        );
+        assert_eq!(
+            "Ax".parse::<Card>(),
+            Err(ParseCardError::WrongSuit(String::from("x")))
+        );
+        assert_eq!(
+            "AsK".parse::<Hand>(),
+            Err(ParseCardError::NoSuit(String::from("K")))
+        );
+        assert_eq!(
+            "AsKd".parse::<Card>(),
+            Err(ParseCardError::WrongCount {
+                expected: "1",
+                got: 2
+            })
+        );
+        assert_eq!(
+            "AsAs".parse::<Hand>(),
+            Err(ParseCardError::DuplicateCard(Card::new(
+                Rank::Ace,
+                Suit::Spades
+            )))
+        );
+        assert!("Ac Kc".parse::<Board>().is_err());
+        assert_eq!(
+            ParseCardError::WrongRank(String::from("Y")).to_string(),
+            "Error: wrong rank 'Y'"
+        );
+    }
+    #[test]
+    fn ready_hand_round_trip() {
+        for value in 1..=HandStrength::CLASSES {
+            let ready_hand = ReadyHand::from(HandStrength::new(value).unwrap());
+            assert_eq!(format!("{:?}", ready_hand).parse(), Ok(ready_hand));
+        }
+        assert_eq!("FlashRoal:".parse(), Ok(ReadyHand::FlashRoal));
+        // Раньше "Streetflash" находился через contains и в "Flash", и в "Street".
+        for (s, err) in [
+            (
+                "FlashRoal",
+                ParseReadyHandError::WrongKind(String::from("FlashRoal")),
+            ),
+            (
+                "XFlash: A K Q J 9",
+                ParseReadyHandError::WrongKind(String::from("XFlash")),
+            ),
+            ("Care: X", ParseReadyHandError::WrongRank(String::from("X"))),
+            (
+                "Street: A K",
+                ParseReadyHandError::WrongCount {
+                    expected: 1,
+                    got: 2,
+                },
+            ),
+        ] {
+            assert_eq!(s.parse::<ReadyHand>(), Err(err), "{}", s);
+        }
+    }
+}
@@ -28,6 +28,9 @@ impl Rank {
     pub fn rnd_rank() -> Self {
         rand::random()
     }
+    pub fn rnd_rank_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        rng.gen()
+    }
     pub fn to_vec_from_low() -> Vec<Self> {
         vec![
             Self::Two,
@@ -142,6 +145,9 @@ impl Suit {
     pub fn rnd_suit() -> Self {
         rand::random()
     }
+    pub fn rnd_suit_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        rng.gen()
+    }
 }
 impl Distribution<Suit> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Suit {
@@ -173,14 +179,28 @@ pub struct Card {
 }
 impl Card {
     pub fn rnd_card() -> Self {
+        Self::rnd_card_with(&mut rand::thread_rng())
+    }
+    pub fn rnd_card_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
         Card {
-            rank: Rank::rnd_rank(),
-            suit: Suit::rnd_suit(),
+            rank: Rank::rnd_rank_with(rng),
+            suit: Suit::rnd_suit_with(rng),
         }
     }
     pub fn new(rank: Rank, suit: Suit) -> Self {
         Card { rank, suit }
     }
+    // Полная колода, 52 карты от двоек к тузам.
+    pub fn deck() -> Vec<Self> {
+        Rank::to_vec_from_low()
+            .into_iter()
+            .flat_map(|rank| {
+                [Suit::Daemonds, Suit::Harts, Suit::Clubs, Suit::Spades]
+                    .map(|suit| Card::new(rank, suit))
+            })
+            .collect()
+    }
+    // Строгий ввод "As", паникует на ошибке. Без паники и с вариантами записи: "as".parse::<Card>().
     pub fn from_string_ui(s: String) -> Self {
         let ss = s.trim();
         if ss.len() != 2 {
@@ -231,30 +251,42 @@ impl Display for Card {
 
 #[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Serialize, Deserialize)]
 pub struct Hand {
-    pub cards: [Card; 4], // always sort from top to low!!!
+    // Количество карт задает вариант игры (GameVariant::hole_cards): 4 для PLO, 5 для PLO5.
+    pub cards: Vec<Card>, // always sort from top to low!!!
 }
 impl Hand {
     pub fn rnd_hand(dead_cards: &Vec<Card>) -> Self {
-        let mut set = HashSet::with_capacity(4);
-        while set.len() < 4 {
-            let card = Card::rnd_card();
+        Self::rnd_hand_with(dead_cards, &mut rand::thread_rng())
+    }
+    pub fn rnd_hand_with<R: Rng + ?Sized>(dead_cards: &Vec<Card>, rng: &mut R) -> Self {
+        Self::rnd_hand_of_size_with(4, dead_cards, rng)
+    }
+    pub fn rnd_hand_of_size_with<R: Rng + ?Sized>(
+        size: usize,
+        dead_cards: &Vec<Card>,
+        rng: &mut R,
+    ) -> Self {
+        let mut set = HashSet::with_capacity(size);
+        while set.len() < size {
+            let card = Card::rnd_card_with(rng);
             if dead_cards.contains(&card) {
                 continue;
             }
             set.insert(card);
         }
-        let mut cards = set.into_iter().collect::<Vec<Card>>();
-        Hand::new(
-            cards.pop().unwrap_or_else(|| unreachable!()),
-            cards.pop().unwrap_or_else(|| unreachable!()),
-            cards.pop().unwrap_or_else(|| unreachable!()),
-            cards.pop().unwrap_or_else(|| unreachable!()),
-        )
-        .unwrap_or_else(|_| unreachable!())
+        Hand::from_cards(set.into_iter().collect()).unwrap_or_else(|_| unreachable!())
     }
     pub fn new(card_1: Card, card_2: Card, card_3: Card, card_4: Card) -> Result<Self, String> {
-        let mut cards = [card_1, card_2, card_3, card_4];
-        let mut uniq_set = HashSet::with_capacity(4);
+        Self::from_cards(vec![card_1, card_2, card_3, card_4])
+    }
+    pub fn from_cards(mut cards: Vec<Card>) -> Result<Self, String> {
+        if !(2..=5).contains(&cards.len()) {
+            return Err(format!(
+                "Error: Can't create preflop hand with {} cards!",
+                cards.len()
+            ));
+        }
+        let mut uniq_set = HashSet::with_capacity(cards.len());
         let is_uniq_cards = cards.iter().all(|e| uniq_set.insert(e));
         if is_uniq_cards {
             cards.sort_unstable_by(|a, b| b.cmp(a));
@@ -285,12 +317,13 @@ impl Hand {
         false
     }
     pub fn is_double_suited(&self) -> bool {
+        // Две масти хотя бы по две карты. Для 4-х карт это ровно 2+2, для PLO5 еще 2+2+1 и 3+2.
         let mut set = HashMap::new();
         for i in self.cards.iter() {
             let elm = set.entry(&i.suit).or_insert(0u8);
             *elm += 1;
         }
-        set.iter().all(|(_, &v)| v == 2)
+        set.values().filter(|&&v| v >= 2).count() >= 2
     }
     pub fn is_off_suited(&self) -> bool {
         let mut set = HashSet::new();
@@ -302,30 +335,26 @@ impl Hand {
             let elm = map.entry(&card.rank).or_insert(0u8);
             *elm += 1;
         });
-        let max = map
-            .iter()
-            .max_by(|&a, &b| a.1.cmp(b.1))
-            .unwrap_or_else(|| unreachable!());
-        let min = map
-            .iter()
-            .min_by(|&a, &b| a.1.cmp(b.1))
-            .unwrap_or_else(|| unreachable!());
+        let max = *map.values().max().unwrap_or_else(|| unreachable!());
+        let pairs = map.values().filter(|&&v| v == 2).count();
 
-        match (max.1, min.1) {
+        match (max, pairs) {
             (1, _) => Pairing::NoPaired,
             (2, 1) => Pairing::Paired,
-            (2, 2) => Pairing::DoublePaired,
+            (2, _) => Pairing::DoublePaired,
             _ => Pairing::TripsCare,
         }
     }
 }
 impl Debug for Hand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{:?} {:?} {:?} {:?}",
-            self.cards[0], self.cards[1], self.cards[2], self.cards[3]
-        )
+        let present = self
+            .cards
+            .iter()
+            .map(|c| format!("{:?}", c))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{}", present)
     }
 }
 
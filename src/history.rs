@@ -0,0 +1,195 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    action, engine::Street, ActionKind, Card, Game, Player, Position, PostflopGame, PreflopGame,
+    TableConfig,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryAction {
+    pub street: Street,
+    pub position: Position,
+    pub action: ActionKind,
+    pub pot_before: Decimal,
+    pub pot_after: Decimal,
+}
+
+/*
+Полная запись раздачи:
+- table - размер стола, старые записи без него 6-max.
+- players - стартовые стеки и карманные карты (как на старте префлопа).
+- board - карты, которые открылись на улице: флоп 3, терн 1, ривер 1. Улицы без карт нет в мапе.
+- actions - все действия по порядку вместе с размером пота до и после.
+- win_loose - итог раздачи, как его вернул eval_result.
+*/
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandHistory {
+    #[serde(default)]
+    pub table: TableConfig,
+    pub players: Vec<Player>,
+    pub board: BTreeMap<Street, Vec<Card>>,
+    pub actions: Vec<HistoryAction>,
+    pub win_loose: HashMap<Position, Decimal>,
+}
+impl HandHistory {
+    pub fn new(preflop_game: &PreflopGame) -> Self {
+        // В игре стеки уже без анте, в записи - стеки до анте, как их принимает from_players.
+        let mut players = preflop_game.players.clone();
+        players.iter_mut().for_each(|player| {
+            if let Some(ante) = preflop_game
+                .main_pot
+                .prev_streets_commits
+                .get(&player.position)
+            {
+                player.stack_size += ante;
+            }
+        });
+        Self {
+            table: preflop_game.table,
+            players,
+            board: BTreeMap::new(),
+            actions: vec![],
+            win_loose: HashMap::new(),
+        }
+    }
+    pub fn push_action(
+        &mut self,
+        street: Street,
+        position: Position,
+        action: ActionKind,
+        pot_before: Decimal,
+        pot_after: Decimal,
+    ) {
+        self.actions.push(HistoryAction {
+            street,
+            position,
+            action,
+            pot_before,
+            pot_after,
+        });
+    }
+    pub fn push_board(&mut self, street: Street, prev_cards: &[Card], cards: &[Card]) {
+        let new_cards = cards
+            .iter()
+            .filter(|c| !prev_cards.contains(c))
+            .copied()
+            .collect::<Vec<Card>>();
+        self.board.insert(street, new_cards);
+    }
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ReplayState {
+    Preflop(PreflopGame),
+    Postflop(Street, PostflopGame),
+}
+
+pub fn replay(history: &HandHistory) -> Result<Vec<ReplayState>, String> {
+    /* Логика:
+    - Первое состояние - старт префлопа, дальше состояние после каждого действия.
+    - Для каждой улицы из board сначала старт улицы (без действий), потом состояния после действий улицы.
+    - Каждое действие проверяется по possible_action_kind и по размеру пота из записи.
+    Если запись не сходится с движком, то Err с номером действия.
+     */
+    let mut states = vec![];
+    let mut dead_cards = vec![];
+    history
+        .players
+        .iter()
+        .for_each(|player| dead_cards.extend_from_slice(&player.hand.cards));
+    let mut preflop_game =
+        PreflopGame::from_players(history.table, history.players.clone(), dead_cards);
+    states.push(ReplayState::Preflop(preflop_game.clone()));
+    let mut actions = history.actions.iter().enumerate().peekable();
+    while let Some((i, act)) = actions.next_if(|(_, a)| a.street == Street::Preflop) {
+        replay_action(&mut preflop_game, i, act)?;
+        states.push(ReplayState::Preflop(preflop_game.clone()));
+    }
+
+    let mut prev_game: Option<PostflopGame> = None;
+    for (&street, new_cards) in history.board.iter() {
+        let mut postflop_game = match &prev_game {
+            None => PostflopGame::from_with_new_cards(&preflop_game, new_cards)?,
+            Some(game) => PostflopGame::from_with_new_cards(game, new_cards)?,
+        };
+        states.push(ReplayState::Postflop(street, postflop_game.clone()));
+        while let Some((i, act)) = actions.next_if(|(_, a)| a.street == street) {
+            replay_action(&mut postflop_game, i, act)?;
+            states.push(ReplayState::Postflop(street, postflop_game.clone()));
+        }
+        prev_game = Some(postflop_game);
+    }
+    if let Some((i, _)) = actions.next() {
+        return Err(format!("Error: action #{} has no street to replay on", i));
+    }
+    Ok(states)
+}
+fn replay_action(game: &mut impl Game, i: usize, act: &HistoryAction) -> Result<(), String> {
+    if game.main_pot().value != act.pot_before {
+        return Err(format!("Error: action #{} pot before mismatch", i));
+    }
+    let possible_act = action::possible_action_kind(game, act.position);
+    if !possible_act.contains(&act.action) {
+        return Err(format!(
+            "Error: action #{} {:?} is not possible for {:?}",
+            i, act.action, act.position
+        ));
+    }
+    game.do_action_on_position(Some(act.action), act.position);
+    if game.main_pot().value != act.pot_after {
+        return Err(format!("Error: action #{} pot after mismatch", i));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DecisionPoint, HandEngine};
+
+    fn limp_then_bet_and_fold(point: &DecisionPoint) -> Option<ActionKind> {
+        // Префлоп все коллят/чекают, на флопе первый ставит, остальные фолдят.
+        let find = |f: fn(&ActionKind) -> bool| point.possible_act.iter().find(|a| f(a)).copied();
+        match point.street {
+            Street::Preflop => find(|a| matches!(a, ActionKind::Call(_) | ActionKind::Check)),
+            _ => find(|a| matches!(a, ActionKind::Raise(_) | ActionKind::Fold)),
+        }
+    }
+    #[test]
+    fn engine_history_replays_to_same_pot() {
+        let mut engine = HandEngine::new(limp_then_bet_and_fold);
+        let outcome = engine.play(PreflopGame::new());
+        let history = outcome.history;
+        assert_eq!(outcome.last_street, Street::Flop);
+        assert_eq!(history.board.get(&Street::Flop).map(|v| v.len()), Some(3));
+
+        let json = history.to_json().unwrap();
+        let history = HandHistory::from_json(&json).unwrap();
+        let states = replay(&history).unwrap();
+        // Старт префлопа + 6 действий, старт флопа + 6 действий (бет и 5 фолдов).
+        assert_eq!(states.len(), 14);
+        let Some(ReplayState::Postflop(Street::Flop, last)) = states.last() else {
+            panic!("last state must be the flop");
+        };
+        assert_eq!(
+            last.main_pot.value,
+            history.actions.last().unwrap().pot_after
+        );
+        assert!(last.end_of_hand_five_foldes());
+    }
+    #[test]
+    fn replay_rejects_impossible_action() {
+        let mut engine = HandEngine::new(limp_then_bet_and_fold);
+        let mut history = engine.play(PreflopGame::new()).history;
+        history.actions[0].action = ActionKind::Check;
+        assert!(replay(&history).is_err());
+    }
+}
@@ -1,13 +1,14 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::BufRead;
 use std::time::Instant;
 use std::{fs, io};
 
-use crate::eval_hand::{self, real_comb};
+use crate::eval_hand::real_comb;
+use crate::hand::SuitMap;
 use crate::postflop_game::fake_postflop::{FakeBoardStruct, FakeSuitPostFlop, Utils};
 use crate::postflop_game::{flop, FakeBoard, FakeStreet, PostflopGame};
-use crate::{preflop, Card, FakeHand, Game, Position, PreflopGame, Rank, ReadyHand, Suit};
+use crate::{preflop, Card, FakeHand, Game, Position, PreflopGame, Rank, Suit};
 
 pub fn test() {
     let MAP_INLINE_RANKS_RIVER: BTreeMap<String, FakeBoard> =
@@ -45,7 +46,7 @@ pub fn inline_fakeboard() {
         let ranks = river.cards.iter().map(|c| c.rank).collect::<Vec<Rank>>();
         map.entry(ranks.clone()).or_insert(fake_board.clone());
 
-        let suits = river.cards.iter().map(|c| c.suit).collect::<Vec<Suit>>();
+        let suits = canonical_suits(&river.cards);
         map_suit.entry(suits.clone()).or_insert(fake_board.clone());
     }
     // for (key, fake_board) in map {
@@ -60,54 +61,6 @@ pub fn inline_fakeboard() {
     //     println!("{}|{:?}", print_key, fake_board.suit_kind);
     // }
 }
-pub fn inline_real_combination() {
-    /*
-    Убрать повторения
-    Разбить как-то расчет, т.к. будет 4 000 000 000 записей
-     */
-    let time = Instant::now();
-    let mut map = BTreeMap::new();
-
-    while map.len() < 2_598_960 {
-        let mut set = HashSet::with_capacity(5);
-        while set.len() < 5 {
-            set.insert(Card::rnd_card());
-        }
-        let mut cards = set.into_iter().collect::<Vec<_>>();
-        cards.sort_unstable_by(|a, b| b.cmp(a));
-
-        if !map.contains_key(&cards) {
-            let comb = eval_hand::combination(&cards);
-            map.insert(cards, comb);
-        }
-    }
-    map.iter().for_each(|(k, v)| {
-        let s = format!(
-            "{:?},{:?},{:?},{:?},{:?}|{:?}",
-            k[0], k[1], k[2], k[3], k[4], v
-        );
-        println!("{}", s);
-    });
-
-    println!("Seconds gone: {}", time.elapsed().as_secs());
-}
-pub fn from_inline_real_combination() -> io::Result<BTreeMap<String, ReadyHand>> {
-    let file = File::open("real_com_river.txt")?;
-    let reader = io::BufReader::new(file);
-
-    let mut map = BTreeMap::new();
-    for line in reader.lines() {
-        let ln = line?;
-        let mut iter = ln.split('|');
-        let key = iter.next().unwrap();
-        let key_comb = iter.next().unwrap();
-
-        let hand = ReadyHand::from_str(key_comb);
-
-        map.insert(key.to_owned(), hand.expect("Can't parse ready hand"));
-    }
-    Ok(map)
-}
 pub fn from_inline_fakeboard_ranks() -> io::Result<BTreeMap<String, FakeBoard>> {
     let file = File::open("fakeboard_ranks_river.txt")?;
     let reader = io::BufReader::new(file);
@@ -180,7 +133,7 @@ pub fn fake_board_from_inline(
         .get(&print_key)
         .expect("Didn't find key-rank from inline map");
 
-    let suits = cards.iter().map(|c| c.suit).collect::<Vec<Suit>>();
+    let suits = canonical_suits(cards);
     let print_key = suits
         .iter()
         .map(|&r| format!("{:?}", r))
@@ -192,3 +145,8 @@ pub fn fake_board_from_inline(
     // fake_board.suit_kind = FakeSuitPostFlop::Rainbow;
     fake_board
 }
+// Ключ по мастям с точностью до их перестановки: ddddd и hhhhh один ключ.
+fn canonical_suits(cards: &[Card]) -> Vec<Suit> {
+    let map = SuitMap::first_seen(cards);
+    cards.iter().map(|c| map.suit(c.suit)).collect()
+}
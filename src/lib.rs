@@ -1,41 +1,71 @@
 use inline::fakeboard;
 use lazy_static::lazy_static;
 use postflop_game::FakeBoard;
-use rust_decimal_macros::dec;
 use std::collections::{BTreeMap, HashMap, HashSet};
 
 pub use action::*;
-pub use eval_hand::ReadyHand;
-pub use hand::{Card, Hand, Rank, Suit};
+pub use cfr::{
+    best_response, head_to_head, BestResponse, CfrTrainer, MatchResult, RiverDeal, Strategy,
+};
+pub use engine::{DecisionPoint, HandEngine, HandOutcome, Street, StreetGame};
+pub use equity::{EquityCalc, EquityResult, PlayerEquity};
+pub use eval_hand::{HandStrength, ReadyHand};
+pub use hand::{Board, Card, Hand, ParseCardError, ParseReadyHandError, Rank, Suit};
 pub use hand::{FakeCard, FakeHand, FakeRank};
+pub use history::{replay, HandHistory, HistoryAction, ReplayState};
+pub use nuts::{
+    blocker_report, hand_rank_among_possible, nut_rank, Blocked, BlockerReport, NutHolding,
+};
+pub use outs::{outs, Out, Outs};
 pub use player::{FakePlayer, FakeStackSize, Player, Position};
 pub use postflop_game::{
     AgroStreet, FakeBoardNew, FakePostReadyHand, FakePostflopFD, FakePostflopHand, FakePostflopNew,
     FakePostflopPause, FakePostflopSD, PostflopGame, Spr,
 };
-pub use preflop_game::{ActionKind, *};
-pub use strategy::{Branch, Node};
+pub use preflop_game::{ActionKind, SidePot, *};
+pub use range::Range;
+pub use strategy::{
+    river_tree, set_river_tree, ActionTree, BetSize, Branch, Node, TreeAction, TreeConfig,
+};
+pub use table::{BlindsConfig, GameVariant, Straddle, TableConfig};
 
 use rust_decimal::Decimal;
 
 pub mod action;
+pub mod cfr;
+pub mod engine;
+pub mod equity;
 pub mod eval_hand;
 pub mod eval_result;
 pub mod hand;
+pub mod history;
 pub mod inline;
+pub mod nuts;
+pub mod outs;
 pub mod player;
 pub mod postflop_game;
 pub mod preflop_game;
+pub mod range;
 pub mod redis;
 pub mod strategy;
+pub mod table;
+#[cfg(test)]
+mod test_utils;
 
 lazy_static! {
     pub static ref MAP_INLINE_RANKS_RIVER: BTreeMap<String, FakeBoard> =
         fakeboard::from_inline_fakeboard_ranks().expect("Didn't find fakeboard_ranks_river.txt");
     pub static ref MAP_INLINE_SUITS_RIVER: BTreeMap<String, FakeBoard> =
         fakeboard::from_inline_fakeboard_suits().expect("Didn't find fakeboard_suits_river.txt");
-    pub static ref MAP_INLINE_REALCOMB: BTreeMap<String, ReadyHand> =
-        fakeboard::from_inline_real_combination().expect("Didn't find real_com_river.txt");
+}
+
+/*
+Генератор для раздачи карт и рандомных действий. Все функции rnd_..._with и ..._with_rng принимают его,
+старые rnd_* без параметра берут thread_rng. Один сид - те же руки, борды и рандомные действия.
+*/
+pub type GameRng = rand::rngs::StdRng;
+pub fn seeded_rng(seed: u64) -> GameRng {
+    rand::SeedableRng::seed_from_u64(seed)
 }
 
 /*
@@ -51,6 +81,7 @@ pub trait Game {
     fn positions_and_money(&self) -> &HashMap<Position, Decimal>;
     fn dead_cards(&self) -> &Vec<Card>;
     fn is_preflop_game(&self) -> bool;
+    fn table(&self) -> TableConfig;
     // For mutate:
     fn main_pot_as_mut_ref(&mut self) -> &mut Pot;
     fn folded_positions_as_mut_ref(&mut self) -> &mut HashSet<Position>;
@@ -106,7 +137,9 @@ pub trait Game {
         false
     }
     fn end_of_hand_five_foldes(&self) -> bool {
-        self.folded_positions().len() >= 5
+        // Название с 6-max: на самом деле все, кроме одного, в фолде для любого стола.
+        self.table()
+            .everyone_else_folded(self.folded_positions().len())
     }
     fn no_money_in_game(&self) -> bool {
         self.positions_and_money().values().sum::<Decimal>() == Decimal::ZERO
@@ -129,13 +162,16 @@ pub trait Game {
                 self.positions_and_money_as_mut_ref().insert(position, x);
                 self.recalculate_min_bet(position);
                 self.recalculate_main_pot();
+                self.recalculate_side_pots();
             }
             ActionKind::Call(x) => {
                 self.positions_and_money_as_mut_ref().insert(position, x);
                 self.recalculate_main_pot();
+                self.recalculate_side_pots();
             }
             ActionKind::Fold => {
                 self.folded_positions_as_mut_ref().insert(position);
+                self.recalculate_side_pots();
             }
             ActionKind::Check => {}
         }
@@ -147,6 +183,94 @@ pub trait Game {
 
         //self.main_pot_as_mut_ref().value = self.positions_and_money().values().sum();
     }
+    fn total_commits(&self) -> HashMap<Position, Decimal> {
+        // Вклад каждой позиции в банк за всю раздачу: прошлые улицы + текущая.
+        let mut commits = self.main_pot().prev_streets_commits.clone();
+        self.positions_and_money()
+            .iter()
+            .for_each(|(&pos, &money)| {
+                *commits.entry(pos).or_insert(Decimal::ZERO) += money;
+            });
+        commits
+    }
+    fn allin_cap(&self, position: Position) -> Option<Decimal> {
+        // Полный вклад игрока, если он в алине (на этой или на прошлых улицах, тогда стек 0).
+        if self.position_in_allin(position) {
+            self.total_commits().get(&position).copied()
+        } else {
+            None
+        }
+    }
+    fn recalculate_side_pots(&mut self) {
+        /* Логика:
+        - Уровни банков это алины не сфолдивших игроков, от меньшего к большему. Последний уровень без ограничения.
+        - В банк уровня попадают деньги всех позиций (и сфолдивших тоже) между прошлым и текущим уровнем.
+        - Выиграть банк могут не сфолдившие, у которых нет алина или алин не меньше уровня.
+        Те, кто еще не ответил на алин, тоже претенденты, т.к. могут заколлить.
+        - Мертвые деньги (которых нет в разбивке по позициям) всегда в основном банке.
+         */
+        let commits = self.total_commits();
+        let mut caps = commits
+            .keys()
+            .filter(|pos| !self.folded_positions().contains(pos))
+            .filter_map(|&pos| self.allin_cap(pos))
+            .collect::<Vec<Decimal>>();
+        caps.sort_unstable();
+        caps.dedup();
+
+        let mut side_pots: Vec<SidePot> = vec![];
+        let mut prev_level = Decimal::ZERO;
+        let levels = caps.iter().map(|&c| Some(c)).chain(std::iter::once(None));
+        for level in levels {
+            let value = commits
+                .values()
+                .map(|&money| {
+                    let top = level.map_or(money, |l| money.min(l));
+                    (top - prev_level).max(Decimal::ZERO)
+                })
+                .sum::<Decimal>();
+            let mut eligible = self
+                .players()
+                .iter()
+                .map(|player| player.position)
+                .filter(|pos| !self.folded_positions().contains(pos))
+                .filter(|&pos| match (self.allin_cap(pos), level) {
+                    (Some(cap), Some(l)) => cap >= l,
+                    (Some(cap), None) => cap > prev_level,
+                    (None, _) => true,
+                })
+                .collect::<Vec<Position>>();
+            eligible.sort_unstable();
+            if value > Decimal::ZERO || side_pots.is_empty() {
+                side_pots.push(SidePot { value, eligible });
+            }
+            if let Some(l) = level {
+                prev_level = l;
+            }
+        }
+        side_pots[0].value += self.main_pot().dead_money();
+        self.main_pot_as_mut_ref().side_pots = side_pots;
+    }
+    fn winnable_pot(&self, position: Position) -> Decimal {
+        /* Банк, который игрок реально может выиграть, если поставит весь свой стек:
+        с каждого соперника не больше, чем мой максимальный вклад, плюс мертвые деньги.
+        Когда все глубже меня, это весь банк.
+         */
+        let commits = self.total_commits();
+        let player = self.player_by_position_as_ref(position);
+        let my_prev = self
+            .main_pot()
+            .prev_streets_commits
+            .get(&position)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+        let my_max_commit = my_prev + player.stack_size;
+        commits
+            .values()
+            .map(|&money| money.min(my_max_commit))
+            .sum::<Decimal>()
+            + self.main_pot().dead_money()
+    }
     fn recalculate_min_bet(&mut self, position: Position) {
         let mut map = self.positions_and_money().clone();
         let act_val = map.remove(&position).unwrap_or_else(|| unreachable!());
@@ -160,7 +284,7 @@ pub trait Game {
         //self.min_bet() = act_val - max_commit;
         self.set_min_bet(act_val - max_commit);
 
-        if self.min_bet() < dec!(1) {
+        if self.min_bet() < self.table().blinds().bb {
             unreachable!()
         }
     }
@@ -172,6 +296,12 @@ mod test {
     use super::player::*;
     use super::postflop_game::PostflopGame;
     use super::preflop_game::*;
+    use super::test_utils::preflop_with_short_allins;
+    use super::{
+        action, redis::RedisUtils, seeded_rng, ActionKind, BlindsConfig, Game, GameVariant,
+        SidePot, Straddle, TableConfig,
+    };
+    use rust_decimal_macros::dec;
     use std::collections::HashMap;
     use std::collections::HashSet;
     #[test]
@@ -261,7 +391,7 @@ mod test {
         assert_eq!(
             hand_sorted,
             Hand {
-                cards: [
+                cards: vec![
                     Card::new(Rank::Ace, Suit::Clubs),
                     Card::new(Rank::Ten, Suit::Daemonds),
                     Card::new(Rank::Two, Suit::Spades),
@@ -277,6 +407,54 @@ mod test {
         }
     }
     #[test]
+    fn same_seed_same_hands_boards_and_actions() {
+        let deal = |seed| {
+            let mut rng = seeded_rng(seed);
+            let preflop_game = PreflopGame::new_with_rng(&mut rng);
+            let flop_game = PostflopGame::from_with_rng(&preflop_game, &mut rng);
+            let river_game = PostflopGame::from_with_rng(
+                &PostflopGame::from_with_rng(&flop_game, &mut rng),
+                &mut rng,
+            );
+            let possible_act = action::possible_action_kind(&preflop_game, Position::Utg);
+            let acts = (0..10)
+                .map(|_| ActionKind::rnd_action_from_with(&possible_act, &mut rng))
+                .collect::<Vec<_>>();
+            (
+                preflop_game.players,
+                river_game.cards,
+                river_game.dead_cards,
+                acts,
+            )
+        };
+        assert_eq!(deal(42), deal(42));
+        assert_ne!(deal(42).1, deal(43).1);
+    }
+    #[test]
+    fn plo5_deals_five_cards_to_everyone() {
+        let table = TableConfig::default().with_variant(GameVariant::Plo5);
+        let game = PreflopGame::new_with_table(table, &vec![], &mut seeded_rng(5));
+        assert_eq!(game.dead_cards.len(), 30);
+        assert!(game.players.iter().all(|p| p.hand.cards.len() == 5));
+        let uniq = game.dead_cards.iter().collect::<HashSet<_>>();
+        assert_eq!(uniq.len(), 30);
+
+        // 2+2+1 по мастям это ds, две пары в пяти картах - DoublePaired.
+        let hand = Hand::from_cards(vec![
+            Card::new(Rank::Ace, Suit::Spades),
+            Card::new(Rank::Ace, Suit::Harts),
+            Card::new(Rank::King, Suit::Spades),
+            Card::new(Rank::King, Suit::Harts),
+            Card::new(Rank::Two, Suit::Clubs),
+        ])
+        .unwrap();
+        let fake = FakeHand::from(&hand);
+        assert_eq!(fake.cards.len(), 5);
+        assert_eq!(fake.kind, FakeSuitKind::Ds);
+        assert_eq!(fake.paired, Pairing::DoublePaired);
+        assert!(Hand::from_cards(vec![Card::new(Rank::Ace, Suit::Spades); 5]).is_err());
+    }
+    #[test]
     fn rnd_hand_and_fakehand_unreachable_never_panic() {
         for _ in 1..1_000 {
             let hand = Hand::rnd_hand(&vec![]);
@@ -370,7 +548,7 @@ mod test {
         assert_eq!(
             FakeHand::from(&hand.unwrap()),
             FakeHand {
-                cards: [
+                cards: vec![
                     FakeCard {
                         rank: FakeRank::MiddleCard
                     },
@@ -398,7 +576,7 @@ mod test {
         assert_eq!(
             FakeHand::from(&hand.unwrap()),
             FakeHand {
-                cards: [
+                cards: vec![
                     FakeCard {
                         rank: FakeRank::Ace
                     },
@@ -426,7 +604,7 @@ mod test {
         assert_eq!(
             FakeHand::from(&hand.unwrap()),
             FakeHand {
-                cards: [
+                cards: vec![
                     FakeCard {
                         rank: FakeRank::King
                     },
@@ -454,7 +632,7 @@ mod test {
         assert_eq!(
             FakeHand::from(&hand.unwrap()),
             FakeHand {
-                cards: [
+                cards: vec![
                     FakeCard {
                         rank: FakeRank::King
                     },
@@ -482,7 +660,7 @@ mod test {
         assert_eq!(
             FakeHand::from(&hand.unwrap()),
             FakeHand {
-                cards: [
+                cards: vec![
                     FakeCard {
                         rank: FakeRank::King
                     },
@@ -510,7 +688,7 @@ mod test {
         assert_eq!(
             FakeHand::from(&hand.unwrap()),
             FakeHand {
-                cards: [
+                cards: vec![
                     FakeCard {
                         rank: FakeRank::Ace
                     },
@@ -626,6 +804,102 @@ mod test {
         assert_eq!(map_cards.values().max(), Some(&1u8));
     }
     #[test]
+    fn bb_ante_and_utg_straddle() {
+        let blinds = BlindsConfig {
+            sb: dec!(1),
+            bb: dec!(2),
+            bb_ante: dec!(2),
+            straddle: Some(Straddle::Utg),
+            ..BlindsConfig::default()
+        };
+        let table = TableConfig::default().with_blinds(blinds).unwrap();
+        let mut game = PreflopGame::new_with_table(table, &vec![], &mut seeded_rng(1));
+        // Блайнды 1 + 2, страддл 4, анте ББ 2 мертвыми деньгами.
+        assert_eq!(game.main_pot.value, dec!(9));
+        assert_eq!(game.main_pot.side_pots[0].value, dec!(9));
+        assert_eq!(game.min_bet, dec!(4));
+        let acts = action::possible_action_kind(&game, Position::Mp);
+        assert_eq!(acts[..2], [ActionKind::Fold, ActionKind::Call(dec!(4))]);
+        for pos in [
+            Position::Mp,
+            Position::Co,
+            Position::Btn,
+            Position::Sb,
+            Position::Bb,
+        ] {
+            game.do_action_on_position(Some(ActionKind::Call(dec!(4))), pos);
+        }
+        // Страддлер ходит последним и может чекнуть.
+        let acts = action::possible_action_kind(&game, Position::Utg);
+        assert_eq!(acts[0], ActionKind::Check);
+        assert_eq!(game.total_commits().get(&Position::Bb), Some(&dec!(6)));
+    }
+    #[test]
+    fn holdem_no_limit_allows_allin_over_pot() {
+        let play = |variant| {
+            let blinds = BlindsConfig {
+                sb: dec!(1),
+                bb: dec!(2),
+                ..BlindsConfig::default()
+            };
+            let table = TableConfig::heads_up()
+                .with_variant(variant)
+                .with_blinds(blinds)
+                .unwrap();
+            let game = PreflopGameBuilder::new(table)
+                .seat(Position::Sb, dec!(100))
+                .seat(Position::Bb, dec!(100))
+                .build_with_rng(&mut seeded_rng(1))
+                .unwrap();
+            action::possible_action_kind(&game, Position::Sb)
+        };
+        let holdem = play(GameVariant::Holdem);
+        assert_eq!(holdem.last(), Some(&ActionKind::Raise(dec!(100))));
+        assert_eq!(holdem.len(), 6);
+        assert_eq!(
+            RedisUtils::get_action_id(ActionKind::Raise(dec!(100)), &holdem),
+            6
+        );
+        assert_eq!(holdem[2], ActionKind::Raise(dec!(6)));
+        assert_eq!(RedisUtils::get_action_id(holdem[2], &holdem), 5);
+        let plo = play(GameVariant::Plo4);
+        assert!(!plo.contains(&ActionKind::Raise(dec!(100))));
+        assert_eq!(plo.len(), 5);
+    }
+    #[test]
+    fn side_pots_by_allin_levels() {
+        let game = preflop_with_short_allins();
+        assert_eq!(
+            game.main_pot.side_pots,
+            vec![
+                SidePot {
+                    value: dec!(31.5),
+                    eligible: vec![Position::Utg, Position::Mp, Position::Co],
+                },
+                SidePot {
+                    value: dec!(80),
+                    eligible: vec![Position::Mp, Position::Co],
+                },
+            ]
+        );
+        assert_eq!(game.main_pot.value, dec!(111.5));
+    }
+    #[test]
+    fn side_pots_carry_to_next_street() {
+        let preflop_game = preflop_with_short_allins();
+        let flop_game = PostflopGame::from(&preflop_game);
+        assert_eq!(
+            flop_game.main_pot.side_pots,
+            preflop_game.main_pot.side_pots
+        );
+    }
+    #[test]
+    fn winnable_pot_limited_by_own_stack() {
+        let game = preflop_with_short_allins();
+        assert_eq!(game.winnable_pot(Position::Utg), dec!(31.5));
+        assert_eq!(game.winnable_pot(Position::Co), dec!(111.5));
+    }
+    #[test]
     fn rnd_flopgame_unreachable_never_panic() {
         for _ in 1..=100 {
             let preflop_game = PreflopGame::new();
@@ -2,27 +2,25 @@ use ::redis::RedisResult;
 use clap::Parser;
 use lazy_static::lazy_static;
 use neiro_om::{
-    action,
+    best_response,
     eval_hand::*,
-    eval_result,
+    eval_result, head_to_head,
     inline::fakeboard,
     postflop_game::{
         eval_fake_hand::{fake_comb_side_fd, fake_comb_side_ready, fake_comb_side_sd},
         fake_postflop::{
             AgroStreet, FakeBoardNew, FakePostflopHand, FakeSuitPostFlop, PotentialFE, Utils,
         },
-        FakeBoard, FakePostflopPause, FakeStreet, PostflopGame,
+        FakeBoard, FakeStreet, PostflopGame,
     },
     preflop_game,
-    redis::{RedisStreet, RedisUtils},
     strategy::GraphPoint,
-    ActionKind, Branch, Card, FakePostReadyHand, FakePostflopNew, FakePreflopPause, Game, Hand,
-    Node, Position, PreflopGame, Spr, MAP_INLINE_RANKS_RIVER, MAP_INLINE_REALCOMB,
-    MAP_INLINE_SUITS_RIVER,
+    Branch, Card, CfrTrainer, DecisionPoint, FakePostReadyHand, FakePostflopNew, Game, GameRng,
+    Hand, HandEngine, Node, Position, PreflopGame, PreflopRanking, RiverDeal, Spr, Street,
+    StreetGame, TreeConfig, MAP_INLINE_RANKS_RIVER, MAP_INLINE_SUITS_RIVER, PREFLOP_RANKING_FILE,
 };
 use rand::Rng;
-use redis::Connection;
-use rust_decimal::Decimal;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use rust_decimal_macros::dec;
 use serde_json;
 use std::{
@@ -38,6 +36,8 @@ use std::{
 
 static DEBUG_REAL_MODE: bool = false;
 static DEBUG_GRAPHS: bool = false;
+// Топ X% для эквити в таблице префлопа.
+const PREFLOP_RANKING_TOP: [u8; 3] = [10, 20, 50];
 
 static mut GLOBAL_GENERATION: u8 = 0;
 static mut PREV_GRAPH: Option<HashMap<FakePostflopNew, Vec<GraphPoint>>> = None;
@@ -52,8 +52,31 @@ struct Args {
     /// Number of times to create new generation. Default = 1.
     #[arg(short, long, default_value_t = 1)]
     count: u8,
+
+    /// Seed for sampling river nodes by frequencies. Random if not set.
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Play river strategies of two generations against each other (b_river_A.txt vs b_river_B.txt) and exit.
+    #[arg(long, num_args = 2, value_names = ["A", "B"])]
+    versus: Option<Vec<u8>>,
+
+    /// Train CFR+ for this number of iterations instead of generations. Default = 0.
+    #[arg(long, default_value_t = 0)]
+    cfr_iterations: u64,
+
+    /// Generate the preflop ranking table with this number of Monte Carlo samples per hand, save it to preflop_ranking.txt and exit. Default = 0.
+    #[arg(long, default_value_t = 0)]
+    preflop_ranking_samples: u64,
+
+    /// JSON file with river tree config (bet_sizes, raise_sizes, max_raises, allin_threshold, variant). Default tree if not set.
+    #[arg(long)]
+    tree_config: Option<String>,
 }
 
+// Фейк, позиция и комбинация игрока в раздаче из river_fake_and_game.txt.
+type RiverSituation = (FakePostflopNew, Position, ReadyHand);
+
 struct ConfigPostflop {
     game: PostflopGame,
     ch_board_str: bool,
@@ -70,21 +93,43 @@ fn main() {
         "Map-suit-river inline loaded size: {}",
         MAP_INLINE_SUITS_RIVER.len()
     );
-    println!(
-        "Map-real-comb inline loaded size: {}",
-        MAP_INLINE_REALCOMB.len()
-    );
-    // fakeboard::inline_real_combination();
     // thread::available_parallelism() = 12
-    // gen_multithread_serde_games(10);
+    // gen_multithread_serde_games(10, None);
     // check_games();
-    // std::process::exit(0);
 
     let args = Args::parse();
+    if args.preflop_ranking_samples > 0 {
+        let ranking = PreflopRanking::generate(
+            args.preflop_ranking_samples,
+            &PREFLOP_RANKING_TOP,
+            args.seed.unwrap_or(0),
+        )
+        .unwrap();
+        ranking.save(PREFLOP_RANKING_FILE).unwrap();
+        println!(
+            "Preflop ranking saved: {} hands to {}",
+            ranking.hands.len(),
+            PREFLOP_RANKING_FILE
+        );
+        return;
+    }
+    // Таблицы может не быть, пока ее не сгенерировали через --preflop-ranking-samples.
+    match PreflopRanking::load(PREFLOP_RANKING_FILE) {
+        Ok(ranking) => println!("Preflop ranking loaded size: {}", ranking.hands.len()),
+        Err(e) => println!("Preflop ranking is not loaded: {}", e),
+    }
 
     println!("Start generation: {}!", args.generation_arg);
     println!("Number of times to create new generation: {}!", args.count);
 
+    // Дерево ривера задается до первой ноды, иначе уже будет дерево по умолчанию.
+    if let Some(path) = &args.tree_config {
+        let contents = std::fs::read_to_string(path).unwrap();
+        let config: TreeConfig = serde_json::from_str(&contents).unwrap();
+        neiro_om::set_river_tree(config).unwrap();
+    }
+    println!("River tree nodes: {}", neiro_om::river_tree().nodes().len());
+
     // Считаю мапу со всеми играми.
     let mut file = std::fs::File::open("river_fake_and_game.txt").unwrap();
     let mut contents = String::new();
@@ -98,6 +143,16 @@ fn main() {
         let river_game: PostflopGame = serde_json::from_str(&k).unwrap();
         games.push((river_game, v));
     }
+    // Результаты всех веток по каждой раздаче: для CFR и для оценки поколений лучшим ответом.
+    let deals = river_deals(&games);
+    if args.cfr_iterations > 0 {
+        train_cfr_river(&deals, args.cfr_iterations);
+        return;
+    }
+    if let Some(versus) = &args.versus {
+        play_generations(&deals, versus[0], versus[1]);
+        return;
+    }
 
     unsafe {
         GLOBAL_GENERATION = args.generation_arg;
@@ -112,24 +167,14 @@ fn main() {
             }
         };
 
-        gen_multithread_preflop_postflop_games(10, games.clone());
+        gen_multithread_preflop_postflop_games(10, games.clone(), args.seed);
+        let generation = unsafe { GLOBAL_GENERATION };
+        print_exploitability(&deals, &read_graph(generation), generation);
         unsafe {
             GLOBAL_GENERATION += 1;
         }
     }
 }
-fn allin_count(game: &PreflopGame) -> u8 {
-    game.positions_and_money
-        .iter()
-        .fold(0u8, |acc, (&pose, &v)| {
-            let player = game.player_by_position_as_ref(pose);
-            if player.stack_size == v {
-                acc + 1
-            } else {
-                acc
-            }
-        })
-}
 fn _print_details_preflop(map: &HashMap<FakePostflopNew, Vec<GraphPoint>>) {
     let mut hand = HashSet::new();
     let mut board = HashSet::new();
@@ -167,6 +212,7 @@ fn _print_details_preflop(map: &HashMap<FakePostflopNew, Vec<GraphPoint>>) {
 fn gen_multithread_preflop_postflop_games(
     workers_count: u8,
     games: Vec<(PostflopGame, Vec<(FakePostflopNew, Position, ReadyHand)>)>,
+    seed: Option<u64>,
 ) {
     let mut result: HashMap<FakePostflopNew, Vec<GraphPoint>> = HashMap::new();
 
@@ -174,9 +220,19 @@ fn gen_multithread_preflop_postflop_games(
     let mut lists = split(games, workers_count);
 
     let mut handles = Vec::new();
-    for _ in 1..=workers_count {
+    // С сидом у каждого потока свой сид (сид + поколение + номер потока), без сида все случайно.
+    let generation = unsafe { GLOBAL_GENERATION };
+    for worker in 1..=workers_count {
         let cur_map = lists.split_off(lists.len() - 1)[0].clone();
-        let handle = thread::spawn(|| gen_games(cur_map));
+        let rng = match seed {
+            // wrapping: большой --seed не должен паниковать в debug.
+            Some(seed) => neiro_om::seeded_rng(
+                seed.wrapping_add(100 * generation as u64)
+                    .wrapping_add(worker as u64),
+            ),
+            None => rand::SeedableRng::from_entropy(),
+        };
+        let handle = thread::spawn(move || gen_games(cur_map, rng));
         handles.push(handle);
     }
     for handle in handles {
@@ -210,7 +266,8 @@ fn gen_multithread_preflop_postflop_games(
 fn serde_result(result: HashMap<FakePostflopNew, Vec<GraphPoint>>) {
     let generation = unsafe { GLOBAL_GENERATION };
     let mut new_map = HashMap::new();
-    for (k, v) in result {
+    for (k, mut v) in result {
+        GraphPoint::set_frequencies(&mut v);
         let k_str = serde_json::to_string(&k).unwrap();
         if let Some(_) = new_map.insert(k_str, v) {
             println!("Doubled");
@@ -235,12 +292,17 @@ fn split(
     result.push(games);
     result
 }
-fn gen_multithread_serde_games(workers_count: u8) {
+fn gen_multithread_serde_games(workers_count: u8, seed: Option<u64>) {
     // (Ключ, действие)(накапливаем сумму результатов, накапливаем счетчик когда встречалось=количество розыгрышей)
+    // С сидом у каждого потока свой сид (seed + номер потока), без сида все случайно.
     let mut result = HashMap::new();
     let mut handles = Vec::new();
-    for _ in 1..=workers_count {
-        let handle = thread::spawn(|| gen_serde_games_river());
+    for worker in 1..=workers_count {
+        let rng = match seed {
+            Some(seed) => neiro_om::seeded_rng(seed.wrapping_add(worker as u64)),
+            None => rand::SeedableRng::from_entropy(),
+        };
+        let handle = thread::spawn(move || gen_serde_games_river(rng));
         handles.push(handle);
     }
     for handle in handles {
@@ -257,6 +319,7 @@ fn gen_multithread_serde_games(workers_count: u8) {
 }
 fn gen_games(
     games: Vec<(PostflopGame, Vec<(FakePostflopNew, Position, ReadyHand)>)>,
+    mut rng: GameRng,
 ) -> HashMap<FakePostflopNew, Vec<GraphPoint>> {
     let cur_gen = unsafe { GLOBAL_GENERATION };
     println!("Thread river games inlined: {}", games.len());
@@ -299,6 +362,7 @@ fn gen_games(
                     &mut real_hands_end_current,
                     &fakes_positions,
                     &prev_gen_graphs,
+                    &mut rng,
                 );
                 // Расчет результата розигрыша по ветке.
                 // println!("real_hands_end {:?}", real_hands_end);
@@ -332,6 +396,7 @@ fn gen_games(
                 &mut real_hands_end_current,
                 &fakes_positions,
                 prev_gen_graphs,
+                &mut rng,
             );
             // Расчет результата розигрыша по ветке.
             // println!("real_hands_end {:?}", real_hands_end);
@@ -365,6 +430,182 @@ fn gen_games(
     fakes_graphs
 }
 
+// Результаты всех веток по раздаче для CFR. Ключи игроков в порядке хода.
+fn river_deal(
+    river_game: &PostflopGame,
+    vec_situation: &[RiverSituation],
+) -> RiverDeal<FakePostflopNew> {
+    let mut fakes_positions = HashMap::new();
+    let mut real_hands_end = HashMap::new();
+    for (fake, position, ready_hand) in vec_situation.iter().take(2) {
+        fakes_positions.insert(*position, fake.clone());
+        real_hands_end.insert(*position, *ready_hand);
+    }
+    // По веткам ноды не выбираются, генератор не используется.
+    let mut rng = neiro_om::seeded_rng(0);
+    let mut first_pose = None;
+    let mut payoffs = HashMap::new();
+    for branch in Branch::all_branches() {
+        let last_node = branch.last_node();
+        let mut real_hands_end_current = real_hands_end.clone();
+        let mut river_game_current = river_game.clone();
+        let nodes_by_poses = play_river(
+            Some(branch),
+            &mut river_game_current,
+            &mut real_hands_end_current,
+            &fakes_positions,
+            &None,
+            &mut rng,
+        );
+        if first_pose.is_none() {
+            first_pose = nodes_by_poses
+                .iter()
+                .find(|(_, nodes)| Node::start_nodes().contains(&nodes[0]))
+                .map(|(&pos, _)| pos);
+        }
+        let winners = eval_result::eval_clear_win_loose(
+            vec![river_game_current.positions_and_money.clone()],
+            &real_hands_end_current,
+            Some(river_game_current.main_pot.prev_street_end_size),
+        );
+        payoffs.insert(last_node, winners);
+    }
+    let first_pose = first_pose.unwrap();
+    let second_pose = *fakes_positions
+        .keys()
+        .find(|&&pos| pos != first_pose)
+        .unwrap();
+    let bb = river_game.table.blinds().bb;
+    let payoffs = payoffs
+        .into_iter()
+        .map(|(node, winners)| {
+            let win = |pos| {
+                (winners.get(&pos).copied().unwrap_or_default() / bb)
+                    .to_f64()
+                    .unwrap()
+            };
+            (node, [win(first_pose), win(second_pose)])
+        })
+        .collect();
+    RiverDeal {
+        keys: [
+            fakes_positions[&first_pose].clone(),
+            fakes_positions[&second_pose].clone(),
+        ],
+        payoffs,
+    }
+}
+
+fn river_deals(games: &[(PostflopGame, Vec<RiverSituation>)]) -> Vec<RiverDeal<FakePostflopNew>> {
+    let time = Instant::now();
+    let chunk_size = games.len() / 10 + 1;
+    let deals = thread::scope(|scope| {
+        let handles = games
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(river_game, vec_situation)| river_deal(river_game, vec_situation))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    println!(
+        "River deals: {}. Seconds gone: {}",
+        deals.len(),
+        time.elapsed().as_secs()
+    );
+    deals
+}
+
+// Тренировка CFR+ по всем раздачам ривера. Средние стратегии пишутся в cfr_river.txt.
+fn train_cfr_river(deals: &[RiverDeal<FakePostflopNew>], iterations: u64) {
+    let time = Instant::now();
+    let mut trainer = CfrTrainer::new();
+    trainer.train(deals, iterations);
+    println!("CFR iterations: {}", trainer.iterations);
+    let result = best_response(deals, |fake, prev| {
+        trainer.average_strategy(fake, prev).unwrap_or_default()
+    });
+    println!(
+        "CFR exploitability: {:.2} BB/100",
+        result.exploitability_bb_100()
+    );
+
+    let mut new_map = HashMap::new();
+    for (k, v) in trainer.average_strategies() {
+        new_map.insert(serde_json::to_string(&k).unwrap(), v);
+    }
+    let content_json_str = serde_json::to_string(&new_map).unwrap();
+    write_to_file(content_json_str, "cfr_river.txt").unwrap();
+    println!("Seconds gone: {}", time.elapsed().as_secs());
+}
+
+/*
+Насколько обыгрывается поколение: лучший ответ на его граф по всем раздачам ривера. Поколение
+лучше прошлого, если эксплуатируемость меньше. Фейков без графа лучший ответ считает игроком,
+который выбирает ноды поровну.
+*/
+fn print_exploitability(
+    deals: &[RiverDeal<FakePostflopNew>],
+    graphs: &HashMap<FakePostflopNew, Vec<GraphPoint>>,
+    generation: u8,
+) {
+    let result = best_response(deals, |fake, prev| {
+        graphs
+            .get(fake)
+            .map(|graph| GraphPoint::distribution(graph, prev))
+            .unwrap_or_default()
+    });
+    println!(
+        "Generation: {}. Best response: first {:.2}, second {:.2} BB/hand. Strategy: first {:.2}, second {:.2} BB/hand. Exploitability: {:.2} BB/100",
+        generation,
+        result.best_response[0],
+        result.best_response[1],
+        result.strategy_value[0],
+        result.strategy_value[1],
+        result.exploitability_bb_100()
+    );
+}
+
+// Матч поколения first против second по всем раздачам ривера, результат для first.
+fn play_generations(deals: &[RiverDeal<FakePostflopNew>], first: u8, second: u8) {
+    let first_graphs = read_graph(first);
+    let second_graphs = read_graph(second);
+    let strategy = |graphs: &HashMap<FakePostflopNew, Vec<GraphPoint>>,
+                    fake: &FakePostflopNew,
+                    prev: Option<Node>| {
+        graphs
+            .get(fake)
+            .map(|graph| GraphPoint::distribution(graph, prev))
+            .unwrap_or_default()
+    };
+    let result = head_to_head(
+        deals,
+        |fake, prev| strategy(&first_graphs, fake, prev),
+        |fake, prev| strategy(&second_graphs, fake, prev),
+    );
+    println!(
+        "Generation {} vs {}: {:.2} ± {:.2} BB/100 on {} duplicate deals{}",
+        first,
+        second,
+        result.bb_100,
+        result.ci_95,
+        result.deals,
+        if result.is_significant() {
+            ""
+        } else {
+            " (not significant)"
+        }
+    );
+}
+
 fn join_graphs(
     fakes_graphs: &mut HashMap<FakePostflopNew, Vec<GraphPoint>>,
     prev_gen_graphs: &Option<HashMap<FakePostflopNew, Vec<GraphPoint>>>,
@@ -426,844 +667,76 @@ fn update_win_in_graf(
         // }
     }
 }
-#[allow(non_snake_case)]
-fn preflop(
-    all_fake_pre: &mut Vec<(Position, String, u8, Decimal)>,
-    debug_real_mode: bool,
-    debug_fake_mode: bool,
-    con: &mut redis::Connection,
-    real_network_player: &Vec<Position>,
-) -> PreflopGame {
-    let GENERATION = unsafe { GLOBAL_GENERATION };
-
-    if debug_real_mode {
-        println!("----------PREFLOP---------");
-    }
-    let poses = vec![
-        Position::Utg,
-        Position::Mp,
-        Position::Co,
-        Position::Btn,
-        Position::Sb,
-        Position::Bb,
-    ];
-    let mut preflop_game = PreflopGame::new();
-    for &position in poses.iter().cycle() {
-        if !preflop_game.folded_positions.contains(&position)
-            && preflop_game.end_of_hand_five_foldes()
-        {
-            if debug_real_mode {
-                println!("All fold, {:?} win!", position);
-            }
-            break;
-        }
-        /* rnd_raise_size нужен чтобы понять каким рейзом рандомно пробуем играть.
-        Я просто вынес этот рандомайзер из процедуры possible_action_kind()
-        Генерирует число от 1 до 3
-        1 => size_75_raise,
-        2 => size_50_raise,
-        _ => size_pot_raise,
-         */
-        let possible_act = action::possible_action_kind(&preflop_game, position);
-
-        /* Заканчиваем розагрыш на этой улице, если или или:
-        1. Все игроки либо в фолде, либо в алине (possible_act кстати пустой)
-        2. Все игроки кроме меня в фолде или олине (possible_act кстати пустой)
-         */
-        if preflop_game.end_of_street(&possible_act, position) {
-            break;
-        }
-        if possible_act.is_empty() {
-            /* Так как это не конец улицы, значит пустой набор возможных действий означает, что эта
-            позиция либо в алине либо в фолде.
-            В таком случае игроку не нужно совершать действие => не нужно делать точку принятия решения
-            и записывать в базу.
-             */
-            continue;
-        }
-        let fake_game_pause = FakePreflopPause::from(&preflop_game, position);
-        // let fake_game_pause = FakePreflopPause::mock();
-        let choosen_act = if GENERATION == 0 || real_network_player.contains(&position) {
-            ActionKind::rnd_action_from(&possible_act)
-        } else {
-            get_act_from_last_gens_pre(&fake_game_pause, &possible_act, con, 3)
-        };
-        if debug_real_mode {
-            let player = preflop_game.player_by_position_as_ref(position);
-            println!(
-                "{:?} ({:?}) [pot.b. {}] [bet {}] -> {:?}",
-                player,
-                possible_act,
-                preflop_game.main_pot.value,
-                preflop_game.min_bet,
-                choosen_act.unwrap(),
-            );
-        }
-        if debug_fake_mode {
-            println!("{:?}", fake_game_pause);
-        }
-        if debug_real_mode {
-            println!(
-                "                                                                               {}",
-                RedisUtils::get_preflop_key(&fake_game_pause, GENERATION)
-            );
-        }
-        if GENERATION == 0 || real_network_player.contains(&position) {
-            all_fake_pre.push((
-                position,
-                RedisUtils::get_preflop_key(&fake_game_pause, GENERATION),
-                RedisUtils::get_action_id(choosen_act.unwrap(), &possible_act),
-                Decimal::ZERO,
-            ));
-        }
-
-        preflop_game.do_action_on_position(choosen_act, position);
-        // println!(
-        //     "{:?} ",
-        //     action::already_commit_by_pos(&preflop_game, position)
-        // );
-    }
-    if debug_real_mode {
-        println!("In all in {}", allin_count(&preflop_game));
-    }
-    return preflop_game;
-}
-#[allow(non_snake_case)]
-fn flop(
-    preflop_game: &PreflopGame,
-    fake_pauses_flop: &mut Vec<(Position, String, u8, Decimal)>,
-    debug_real_mode: bool,
-    debug_fake_mode: bool,
-    con: &mut redis::Connection,
-    real_network_player: &Vec<Position>,
-    prev_agr_pose: Option<Position>,
-) -> PostflopGame {
-    let GENERATION = unsafe { GLOBAL_GENERATION };
-
-    if debug_real_mode {
-        println!("----------FLOP---------");
-    }
-    let poses = vec![
-        Position::Sb,
-        Position::Bb,
-        Position::Utg,
-        Position::Mp,
-        Position::Co,
-        Position::Btn,
-    ];
-    let mut flop_game = PostflopGame::from(preflop_game);
-    // Fakes+
-    let fake_board = Utils::new_fake_flop_board(&flop_game);
-    let ch_board_str = false;
-    let mut fake_hands = HashMap::new();
-    poses
-        .iter()
-        .filter(|&pos| !flop_game.folded_positions.contains(&pos))
-        .for_each(|&pos| {
-            let player = flop_game.player_by_position_as_ref(pos);
-            let combination = real_comb(&player.hand, &flop_game.cards);
-            let fake_hand = FakePostflopHand {
-                ready: fake_comb_side_ready(&player.hand, combination, &flop_game.cards),
-                flash_draw: fake_comb_side_fd(&player.hand, combination, &flop_game.cards),
-                street_draw: fake_comb_side_sd(&player.hand, combination, &flop_game.cards),
-            };
-            fake_hands.insert(pos, fake_hand);
-        });
-    // Fakes-
-    if debug_real_mode {
-        println!("{:?}", flop_game);
-    }
-    let mut cyrcle_count = 0_u8;
-    for &position in poses.iter().cycle() {
-        if position == Position::Sb {
-            cyrcle_count += 1;
-        }
-        if !flop_game.folded_positions.contains(&position) && flop_game.end_of_hand_five_foldes() {
-            if debug_real_mode {
-                println!("All fold, {:?} win!", position);
-            }
-            break;
-        }
-        // Если все кто мог сделать экшн чекнули на постфлопе, то заканчиваем улицу и переходим на следующую.
-        if cyrcle_count > 1 && flop_game.no_money_in_game() {
-            if debug_real_mode {
-                println!("All checks who can");
-            }
-            break;
-        }
-        let player = flop_game.player_by_position_as_ref(position);
-        let possible_act = action::possible_action_kind(&flop_game, position);
-        if flop_game.end_of_street(&possible_act, position) {
-            break;
-        }
-        if possible_act.is_empty() {
-            /* Так как это не конец улицы, значит пустой набор возможных действий означает, что эта
-            позиция либо в алине либо в фолде.
-            В таком случае игроку не нужно совершать действие => не нужно делать точку принятия решения
-            и записывать в базу.
-            */
-            continue;
-        }
-        // Fakes+
-        let fake_situation = Utils::postflop_situation(&flop_game, player);
-        let fake_game_pause = FakePostflopPause::from_parts(
-            *fake_hands
-                .get(&position)
-                .expect("Fake hands not evaluate for pose"),
-            fake_board,
-            fake_situation,
-            ch_board_str,
-            AgroStreet::calculate(&prev_agr_pose, position),
-        );
-        // let fake_game_pause = FakePostflopPause::mock();
-        // Fakes-
-        let choosen_act = if GENERATION == 0 || real_network_player.contains(&position) {
-            ActionKind::rnd_action_from(&possible_act)
-        } else {
-            get_act_from_last_gens(&fake_game_pause, &RedisStreet::Flop, &possible_act, con, 3)
-        };
-        if debug_real_mode {
-            let combination = real_comb(&player.hand, &flop_game.cards);
-            println!(
-                "{:?} {:?} ({:?}) [pot.b. {}] [m.bet {}] -> {:?}",
-                player,
-                combination,
-                possible_act,
-                flop_game.main_pot.value,
-                flop_game.min_bet,
-                choosen_act.unwrap(),
-            );
-        }
-        if debug_fake_mode {
-            println!("{:?}", fake_game_pause);
-        }
-        if debug_real_mode {
-            println!(
-                "                                                                               {}",
-                RedisUtils::get_postflop_key(&fake_game_pause, GENERATION, &RedisStreet::Flop)
-            );
-        }
-        if GENERATION == 0 || real_network_player.contains(&position) {
-            fake_pauses_flop.push((
-                position,
-                RedisUtils::get_postflop_key(&fake_game_pause, GENERATION, &RedisStreet::Flop),
-                RedisUtils::get_action_id(choosen_act.unwrap(), &possible_act),
-                Decimal::ZERO,
-            ));
-        }
-        // Fakes-
-
-        flop_game.do_action_on_position(choosen_act, position);
-
-        //println!("{:?} ", action::already_commit_by_pos(&flop_game, position));
-    }
-    return flop_game;
-}
-#[allow(non_snake_case)]
-fn turn(
-    flop_game: &PostflopGame,
-    fake_pauses_turn: &mut Vec<(Position, String, u8, Decimal)>,
-    debug_real_mode: bool,
-    debug_fake_mode: bool,
-    con: &mut redis::Connection,
-    real_network_player: &Vec<Position>,
-    prev_agr_pose: &mut Option<Position>,
-) -> PostflopGame {
-    let GENERATION = unsafe { GLOBAL_GENERATION };
-    let mut current_agr = None;
-
-    if debug_real_mode {
-        println!("----------TURN---------");
-    }
-    let poses = vec![
-        Position::Sb,
-        Position::Bb,
-        Position::Utg,
-        Position::Mp,
-        Position::Co,
-        Position::Btn,
-    ];
-    let mut turn_game = PostflopGame::from(flop_game);
-    // Fakes+
-    let fake_board = Utils::new_fake_flop_board(&turn_game);
-    let prev_fake_board = Utils::new_fake_flop_board(&flop_game);
-    if debug_real_mode {
-        println!("Prev board {:?}", turn_game.cards);
-    }
-    let ch_board_str = fake_board != prev_fake_board;
-    let mut fake_hands = HashMap::new();
-    poses
-        .iter()
-        .filter(|&pos| !turn_game.folded_positions.contains(&pos))
-        .for_each(|&pos| {
-            let player = turn_game.player_by_position_as_ref(pos);
-            let combination = real_comb(&player.hand, &turn_game.cards);
-            let fake_hand = FakePostflopHand {
-                ready: fake_comb_side_ready(&player.hand, combination, &turn_game.cards),
-                flash_draw: fake_comb_side_fd(&player.hand, combination, &turn_game.cards),
-                street_draw: fake_comb_side_sd(&player.hand, combination, &turn_game.cards),
-            };
-            fake_hands.insert(pos, fake_hand);
-        });
-    // Fakes-
-    if debug_real_mode {
-        println!("{:?}", turn_game);
-    }
-    let mut cyrcle_count = 0_u8;
-    for &position in poses.iter().cycle() {
-        if position == Position::Sb {
-            cyrcle_count += 1;
-        }
-        if !turn_game.folded_positions.contains(&position) && turn_game.end_of_hand_five_foldes() {
-            if debug_real_mode {
-                println!("All fold, {:?} win!", position);
-            }
-            break;
-        }
-        // Если все кто мог сделать экшн чекнули на постфлопе, то заканчиваем улицу и переходим на следующую.
-        if cyrcle_count > 1 && turn_game.no_money_in_game() {
-            if debug_real_mode {
-                println!("All checks who can");
-            }
-            break;
-        }
-        let player = turn_game.player_by_position_as_ref(position);
-        let possible_act = action::possible_action_kind(&turn_game, position);
-
-        if turn_game.end_of_street(&possible_act, position) {
-            break;
-        }
-        if possible_act.is_empty() {
-            /* Так как это не конец игры, значит пустой набор возможных действий означает, что эта
-            позиция либо в алине либо в фолде.
-            В таком случае игроку не нужно совершать действие => не нужно делать точку принятия решения
-            и записывать в базу.
-             */
-            continue;
-        }
-        // Faks+
-        let fake_situation = Utils::postflop_situation(&turn_game, player);
-        let fake_game_pause = FakePostflopPause::from_parts(
-            *fake_hands
-                .get(&position)
-                .expect("Fake hands not evaluate for pose"),
-            fake_board,
-            fake_situation,
-            ch_board_str,
-            AgroStreet::calculate(&prev_agr_pose, position),
-        );
-        // let fake_game_pause = FakePostflopPause::mock();
-        // Faks-
-        let choosen_act = if GENERATION == 0 || real_network_player.contains(&position) {
-            ActionKind::rnd_action_from(&possible_act)
-        } else {
-            let rnd_deep_search = match GENERATION {
-                1 => 1u8,
-                _ => 2,
-            };
-            if debug_real_mode {
-                println!(
-                    "           ------ Try to find in prev gen: {}",
-                    RedisUtils::get_postflop_key(
-                        &fake_game_pause,
-                        GENERATION - 1,
-                        &RedisStreet::Turn
-                    )
-                );
-            }
-            get_act_from_last_gens(
-                &fake_game_pause,
-                &RedisStreet::Turn,
-                &possible_act,
-                con,
-                rnd_deep_search,
-            )
-        };
-        if debug_real_mode {
-            let combination = real_comb(&player.hand, &turn_game.cards);
-            println!(
-                "{:?} {:?} ({:?}) [pot.b. {}] [m.bet {}] -> {:?}",
-                player,
-                combination,
-                possible_act,
-                turn_game.main_pot.value,
-                turn_game.min_bet,
-                choosen_act.unwrap(),
-            );
-        }
-        if debug_fake_mode {
-            println!(
-                "           ------ We choose action: {}",
-                RedisUtils::get_action_id(choosen_act.unwrap(), &possible_act)
-            );
-            println!("{:?}", fake_game_pause)
-        };
-        if GENERATION == 0 || real_network_player.contains(&position) {
-            fake_pauses_turn.push((
-                position,
-                RedisUtils::get_postflop_key(&fake_game_pause, GENERATION, &RedisStreet::Turn),
-                RedisUtils::get_action_id(choosen_act.unwrap(), &possible_act),
-                Decimal::ZERO,
-            ));
-        }
-        // Fakes-
-
-        turn_game.do_action_on_position(choosen_act, position);
-
-        if let ActionKind::Raise(_) = choosen_act.unwrap() {
-            current_agr = Some(position);
-        }
-
-        //println!("{:?} ", action::already_commit_by_pos(&flop_game, position));
-    }
-    *prev_agr_pose = current_agr;
-    return turn_game;
-}
 fn play_river(
     branch: Option<Branch>,
     river_game: &mut PostflopGame,
     real_hands_end: &mut HashMap<Position, ReadyHand>,
     fakes_positions: &HashMap<Position, FakePostflopNew>,
     prev_gen_graphs: &Option<HashMap<FakePostflopNew, Vec<GraphPoint>>>,
+    rng: &mut GameRng,
 ) -> HashMap<Position, Vec<Node>> {
     if DEBUG_REAL_MODE {
         println!("----------RIVER---------");
         println!("----------BR: {:?}", branch);
-    }
-    let poses = vec![
-        Position::Sb,
-        Position::Bb,
-        Position::Utg,
-        Position::Mp,
-        Position::Co,
-        Position::Btn,
-    ];
-    if DEBUG_REAL_MODE {
         println!("{:?}", river_game);
     }
     let mut nodes_by_poses: HashMap<Position, Vec<Node>> = HashMap::new();
-    let mut action_count = 0_usize;
     let mut prev_node = None;
-    let mut cyrcle_count = 0_u8;
-    for &position in poses.iter().cycle() {
-        if position == Position::Sb {
-            cyrcle_count += 1;
-        }
-        let all_fold_or_allin = river_game
-            .positions_and_money()
-            .iter()
-            .all(|(&pos, &money)| {
-                river_game.player_by_position_as_ref(pos).stack_size == money
-                    || river_game.folded_positions().contains(&pos)
-            });
-        if all_fold_or_allin {
-            break;
-        }
-        if river_game.folded_positions().contains(&position)
-            || river_game.position_in_allin(position)
-        {
-            continue;
-        }
-
-        let possible_act = action::possible_action_kind(river_game, position);
-        if !river_game.folded_positions().contains(&position) && possible_act.is_empty() {
-            /* Если по какой-то причине пустой набор вариантов возможных действий, то это паника в селе, спятил дед
-             */
-            break;
-        }
-        // Если все кто мог сделать экшн чекнули на постфлопе, то заканчиваем улицу и переходим на следующую.
-        if cyrcle_count > 1 && river_game.no_money_in_game() {
+    /* Улицу играет HandEngine, здесь только выбор ноды: по ветке или по графу прошлого поколения.
+    Ветка кончилась, а улица нет - движок сдает позицию (чек или фолд).
+    Карты на ривере уже все, поэтому сид движка ни на что не влияет. */
+    let mut engine = HandEngine::with_seed(
+        |point: &DecisionPoint| {
+            let StreetGame::Postflop(game) = point.game else {
+                unreachable!()
+            };
+            let action_count = nodes_by_poses
+                .values()
+                .map(|nodes| nodes.len())
+                .sum::<usize>();
+            let node = match &branch {
+                Some(branch) => *branch.path.get(action_count)?,
+                None => {
+                    let cur_fake = fakes_positions.get(&point.position).unwrap();
+                    let prev_graphs = prev_gen_graphs.clone().unwrap();
+                    best_node(cur_fake, prev_node, prev_graphs, rng)
+                }
+            };
+            let act = Node::action_from_node(node, game, point.position, point.possible_act);
             if DEBUG_REAL_MODE {
-                println!("All checks who can");
+                let player = game.player_by_position_as_ref(point.position);
+                let combination = real_comb(&player.hand, &game.cards);
+                println!(
+                    "{:?} {:?} ({:?}) [pot {}] [m.bet {}] -> {:?}",
+                    player, combination, point.possible_act, game.main_pot.value, game.min_bet, act,
+                );
             }
-            break;
-        }
-        if possible_act.is_empty() {
-            /* Так как это не конец игры, значит пустой набор возможных действий означает, что эта
-            позиция либо в алине либо в фолде.
-            В таком случае игроку не нужно совершать действие => не нужно делать точку принятия решения
-            и записывать в базу.
-             */
-            continue;
-        }
-
-        let node = if let Some(branch) = &branch {
-            let Some(&node) = branch.path.get(action_count) else {
-                break;
-            };
-            node
-        } else {
-            let cur_fake = fakes_positions.get(&position).unwrap();
-            let prev_graphs = prev_gen_graphs.clone().unwrap();
-            best_node(cur_fake, prev_node, prev_graphs)
-            // Node::B100
-        };
-        let act = Node::action_from_node(node, river_game.main_pot.value, &possible_act);
-
-        if DEBUG_REAL_MODE {
-            let player = river_game.player_by_position_as_ref(position);
-            let combination = real_comb(&player.hand, &river_game.cards);
-            println!(
-                "{:?} {:?} ({:?}) [pot {}] [m.bet {}] -> {:?}",
-                player,
-                combination,
-                possible_act,
-                river_game.main_pot.value,
-                river_game.min_bet,
-                act,
-            );
-        }
-
-        river_game.do_action_on_position(Some(act), position);
-
-        action_count += 1;
-        prev_node = Some(node);
-        nodes_by_poses
-            .entry(position)
-            .and_modify(|v| v.push(node))
-            .or_insert(vec![node]);
-        //println!("{:?} ", action::already_commit_by_pos(&flop_game, position));
-    }
+            prev_node = Some(node);
+            nodes_by_poses.entry(point.position).or_default().push(node);
+            Some(act)
+        },
+        0,
+    );
+    engine.play_street(river_game, Street::River);
     // За розыгрыш ривера могут сфолдить, поэтому из real_hands_end они исключаются
     // потомучто там должны храниться только комбинации между которых будет делиться банк
-    poses
-        .iter()
-        .filter(|&pos| river_game.folded_positions().contains(&pos))
-        .for_each(|&pos| {
-            real_hands_end.remove(&pos);
-        });
+    real_hands_end.retain(|pos, _| !river_game.folded_positions().contains(pos));
     nodes_by_poses
 }
-
+// Нода по частотам графа прошлого поколения (смешанная стратегия), а не лучшая по винрейту.
 fn best_node(
     cur_fake: &FakePostflopNew,
     prev_node: Option<Node>,
     prev_graphs: HashMap<FakePostflopNew, Vec<GraphPoint>>,
+    rng: &mut GameRng,
 ) -> Node {
-    let possible_nodes = if let Some(p_node) = prev_node {
-        p_node.childrens()
-    } else {
-        Node::start_nodes()
-    };
     let graph = prev_graphs.get(cur_fake).unwrap();
     if DEBUG_GRAPHS {
         println!("---------{:?}--------", cur_fake);
-        let debg = graph
-            .iter()
-            .cloned()
-            .filter(|x| possible_nodes.contains(&x.node))
-            .collect::<Vec<_>>();
-        println!("{:#?}", debg);
-    }
-    graph
-        .iter()
-        .filter(|x| possible_nodes.contains(&x.node))
-        .max_by(|&&x, &&y| {
-            let wr_x = if x.hands != 0 {
-                x.win / Decimal::from(x.hands)
-            } else {
-                Decimal::ZERO
-            };
-            let wr_y = if y.hands != 0 {
-                y.win / Decimal::from(y.hands)
-            } else {
-                Decimal::ZERO
-            };
-            wr_x.cmp(&wr_y)
-        })
-        .unwrap()
-        .node
-}
-#[allow(non_snake_case)]
-fn river(
-    turn_game: &PostflopGame,
-    fake_pauses_river: &mut Vec<(Position, String, u8, Decimal)>,
-    debug_real_mode: bool,
-    debug_fake_mode: bool,
-    real_hands_end: &mut HashMap<Position, ReadyHand>,
-    con: &mut redis::Connection,
-    real_network_player: &Vec<Position>,
-    prev_agr_pose: Option<Position>,
-) -> PostflopGame {
-    let GENERATION = unsafe { GLOBAL_GENERATION };
-    if debug_real_mode {
-        println!("----------RIVER---------");
-    }
-    let poses = vec![
-        Position::Sb,
-        Position::Bb,
-        Position::Utg,
-        Position::Mp,
-        Position::Co,
-        Position::Btn,
-    ];
-    let mut river_game = PostflopGame::from(turn_game);
-    // Fakes+
-    let fake_board = Utils::new_fake_flop_board(&river_game);
-    let prev_fake_board = Utils::new_fake_flop_board(&turn_game);
-    if debug_real_mode {
-        println!("Prev board {:?}", turn_game.cards);
-    }
-    let ch_board_str = fake_board != prev_fake_board;
-    // let fake_board = Utils::fake_flop_board_inline(
-    //     &river_game,
-    //     &MAP_INLINE_RANKS_RIVER,
-    //     &MAP_INLINE_SUITS_RIVER,
-    // );
-    let mut fake_hands = HashMap::new();
-    poses
-        .iter()
-        .filter(|&pos| !river_game.folded_positions().contains(&pos))
-        .for_each(|&pos| {
-            let player = river_game.player_by_position_as_ref(pos);
-            let combination = real_comb(&player.hand, &river_game.cards);
-            let fake_hand = FakePostflopHand {
-                ready: fake_comb_side_ready(&player.hand, combination, &river_game.cards),
-                flash_draw: fake_comb_side_fd(&player.hand, combination, &river_game.cards),
-                street_draw: fake_comb_side_sd(&player.hand, combination, &river_game.cards),
-            };
-            fake_hands.insert(pos, fake_hand);
-            real_hands_end.insert(pos, combination);
-        });
-    // Fakes-
-    if debug_real_mode {
-        println!("{:?}", river_game);
-    }
-    let mut cyrcle_count = 0_u8;
-    for &position in poses.iter().cycle() {
-        if position == Position::Sb {
-            cyrcle_count += 1;
-        }
-        if !river_game.folded_positions.contains(&position) && river_game.end_of_hand_five_foldes()
-        {
-            if debug_real_mode {
-                println!("All fold, {:?} win!", position);
-            }
-            break;
-        }
-        // Если все кто мог сделать экшн чекнули на постфлопе, то заканчиваем улицу и переходим на следующую.
-        if cyrcle_count > 1 && river_game.no_money_in_game() {
-            if debug_real_mode {
-                println!("All checks who can");
-            }
-            break;
-        }
-        let player = river_game.player_by_position_as_ref(position);
-        let possible_act = action::possible_action_kind(&river_game, position);
-
-        if river_game.end_of_street(&possible_act, position) {
-            break;
-        }
-        if possible_act.is_empty() {
-            /* Так как это не конец игры, значит пустой набор возможных действий означает, что эта
-            позиция либо в алине либо в фолде.
-            В таком случае игроку не нужно совершать действие => не нужно делать точку принятия решения
-            и записывать в базу.
-             */
-            continue;
-        }
-        // Faks+
-        let fake_situation = Utils::postflop_situation(&river_game, player);
-        let fake_game_pause = FakePostflopPause::from_parts(
-            *fake_hands
-                .get(&position)
-                .expect("Fake hands not evaluate for pose"),
-            fake_board,
-            fake_situation,
-            ch_board_str,
-            AgroStreet::calculate(&prev_agr_pose, position),
-        );
-        // let fake_game_pause = FakePostflopPause::mock();
-        // Faks-
-        let choosen_act: Option<ActionKind> =
-            if GENERATION == 0 || real_network_player.contains(&position) {
-                ActionKind::rnd_action_from(&possible_act)
-            } else {
-                let rnd_deep_search = match GENERATION {
-                    1 => 1u8,
-                    _ => 1,
-                };
-                if debug_real_mode {
-                    println!(
-                        "           ------ Try to find in prev gen: {}",
-                        RedisUtils::get_postflop_key(
-                            &fake_game_pause,
-                            GENERATION - 1,
-                            &RedisStreet::River
-                        )
-                    );
-                }
-                get_act_from_last_gens(
-                    &fake_game_pause,
-                    &RedisStreet::River,
-                    &possible_act,
-                    con,
-                    rnd_deep_search,
-                )
-            };
-        if debug_real_mode {
-            let combination = real_comb(&player.hand, &river_game.cards);
-            println!(
-                "{:?} {:?} ({:?}) [pot.b. {}] [m.bet {}] -> {:?}",
-                player,
-                combination,
-                possible_act,
-                river_game.main_pot.value,
-                river_game.min_bet,
-                choosen_act.unwrap(),
-            );
-        }
-        if debug_fake_mode {
-            println!(
-                "           ------ We choose action: {}",
-                RedisUtils::get_action_id(choosen_act.unwrap(), &possible_act)
-            );
-            println!("{:?}", fake_game_pause)
-        };
-        if GENERATION == 0 || real_network_player.contains(&position) {
-            fake_pauses_river.push((
-                position,
-                RedisUtils::get_postflop_key(&fake_game_pause, GENERATION, &RedisStreet::River),
-                RedisUtils::get_action_id(choosen_act.unwrap(), &possible_act),
-                Decimal::ZERO,
-            ));
-        }
-        // Fakes-
-
-        river_game.do_action_on_position(choosen_act, position);
-
-        //println!("{:?} ", action::already_commit_by_pos(&flop_game, position));
+        println!("{:#?}", GraphPoint::distribution(graph, prev_node));
     }
-    // За розыгрыш ривера могут сфолдить, поэтому из real_hands_end они исключаются
-    // потомучто там должны храниться только комбинации между которых будет делиться банк
-    poses
-        .iter()
-        .filter(|&pos| river_game.folded_positions().contains(&pos))
-        .for_each(|&pos| {
-            real_hands_end.remove(&pos);
-        });
-    return river_game;
+    GraphPoint::sample_node(graph, prev_node, rng)
 }
-fn rnd_raise_size() -> u8 {
-    rand::Rng::gen_range(&mut rand::thread_rng(), 1u8..=3u8)
-}
-fn rnd_raise_size_to_string(rnd_raise_size: u8, choosen_act: Option<ActionKind>) -> String {
-    /*
-    - Для любого действия, кроме рейза не имеет смысла и выводится пустая строка
-    - Для рейза код полностью ОБЯЗАН повторять код из процедуры action::possible_action_kind()
-     */
-    if let Some(ActionKind::Raise(_)) = choosen_act {
-        match rnd_raise_size {
-            1 => "75%".to_string(),
-            2 => "50%".to_string(),
-            _ => "100%".to_string(),
-        }
-    } else {
-        String::new()
-    }
-}
-fn add_wins_to_redis_fakes(
-    fakes: &mut Vec<(Position, String, u8, Decimal)>,
-    winners: &HashMap<Position, Decimal>,
-    all_fakes: &mut BTreeMap<(String, u8), (Decimal, Decimal)>,
-) {
-    // На старте последний элемент кортежа всегда 0. Вот здесь его заполняем.
-    for (pose, _, _, result) in fakes.iter_mut() {
-        let win = winners
-            .get(pose)
-            .expect("error: Didn't find win/loose in pose");
-        *result = *win;
-    }
-    // Добавляем в список записей все фейковые структуры.
-    for (_, key_fake, id_action, result) in fakes {
-        let hands_result = all_fakes
-            .entry((key_fake.to_owned(), *id_action))
-            .or_insert((Decimal::ZERO, Decimal::ZERO));
-        hands_result.0 += *result;
-        hands_result.1 += dec!(1);
-    }
-}
-#[allow(non_snake_case)]
-fn get_act_from_last_gens(
-    fake_game_pause: &FakePostflopPause,
-    street: &RedisStreet,
-    possible_act: &Vec<ActionKind>,
-    con: &mut Connection,
-    number_las_gens: u8,
-) -> Option<ActionKind> {
-    assert_ne!(street, &RedisStreet::Preflop);
-    let GENERATION = unsafe { GLOBAL_GENERATION };
-    // println!("----");
-    /*
-    Пробный алгоритм для того, чтобы не было четное поколение супер тайт, нечетное супер агро.
-    Пусть у меня нпс-игрок будет играть случайно по прошлому или позапрошлому поколению (number_las_gens = 2), кроме GENERATION = 1
-     */
-    let number = rand::Rng::gen_range(&mut rand::thread_rng(), 1..=number_las_gens);
-    let post_key = RedisUtils::get_postflop_key(fake_game_pause, GENERATION - number, street);
-    // Ошибка только если ошибка подключения в редиске. Если нет ключа/действия, то Ok(None)
-    let act = RedisUtils::best_action(possible_act, post_key, con).unwrap();
-    if act.is_some() {
-        // println!(
-        //     "{}",
-        //     RedisUtils::get_postflop_key(fake_game_pause, GENERATION - 1, street)
-        // );
-        // println!("notzero#{}", GENERATION);
-        return act;
-    }
-    let post_key = RedisUtils::get_postflop_key(fake_game_pause, 0, street);
-    // Ошибка только если ошибка подключения в редиске. Если нет ключа/действия, то Ok(None)
-    let act = RedisUtils::best_action(possible_act, post_key, con).unwrap();
-    if act.is_some() {
-        // println!(
-        //     "{}",
-        //     RedisUtils::get_postflop_key(fake_game_pause, 0, street)
-        // );
-        // println!("zero#{}", GENERATION);
-        return act;
-    }
-
-    // for number in 1..=number_las_gens {
-    //     let post_key = RedisUtils::get_postflop_key(fake_game_pause, GENERATION - number, street);
-    //     // Ошибка только если ошибка подключения в редиске. Если нет ключа/действия, то Ok(None)
-    //     let act = RedisUtils::best_action(possible_act, post_key, con).unwrap();
-    //     if act.is_some() {
-    //         // println!(
-    //         //     "{}",
-    //         //     RedisUtils::get_postflop_key(fake_game_pause, GENERATION - 1, street)
-    //         // );
-    //         return act;
-    //     }
-    // }
-
-    println!(
-        "#rnd{}+ {}",
-        GENERATION,
-        RedisUtils::get_postflop_key(fake_game_pause, GENERATION - number, street)
-    );
-    ActionKind::rnd_action_from(possible_act)
-}
-#[allow(non_snake_case)]
-fn get_act_from_last_gens_pre(
-    fake_game_pause: &FakePreflopPause,
-    possible_act: &Vec<ActionKind>,
-    con: &mut Connection,
-    number_las_gens: u8,
-) -> Option<ActionKind> {
-    // println!("----");
-    let GENERATION = unsafe { GLOBAL_GENERATION };
-    for number in 1..=number_las_gens {
-        let pre_key = RedisUtils::get_preflop_key(fake_game_pause, GENERATION - number);
-        // Ошибка только если ошибка подключения в редиске. Если нет ключа/действия, то Ok(None)
-        let act = RedisUtils::best_action(&possible_act, pre_key, con).unwrap();
-        if act.is_some() {
-            // println!("#{}+", GENERATION - number);
-            return act;
-        }
-    }
-    // println!("#rnd+");
-    ActionKind::rnd_action_from(&possible_act)
-}
-
 #[allow(dead_code)]
 fn print_end_redis_fakes(records: &BTreeMap<(String, u8), (Decimal, Decimal)>) {
     let mut print_key = None;
@@ -1290,31 +763,27 @@ fn print_end_redis_fakes(records: &BTreeMap<(String, u8), (Decimal, Decimal)>) {
     }
 }
 #[allow(dead_code)]
-fn syntetic_preflop(lock_cards: &Vec<Card>) -> PreflopGame {
-    if lock_cards.is_empty() {
-        PreflopGame::new()
-    } else {
-        PreflopGame::new_with_lock_cards(lock_cards)
-    }
+fn syntetic_preflop(lock_cards: &Vec<Card>, rng: &mut GameRng) -> PreflopGame {
+    PreflopGame::new_with_lock_cards_and_rng(lock_cards, rng)
 }
 #[allow(dead_code)]
-fn syntetic_postflop(init_game: &impl Game) -> PostflopGame {
+fn syntetic_postflop(init_game: &impl Game, rng: &mut GameRng) -> PostflopGame {
     // Everybody cheched. No need to modify any property of the game state machine(pot,map,folded,...)
     // let bottles = if is_friday { 3 } else { 1 };
-    PostflopGame::from(init_game)
+    PostflopGame::from_with_rng(init_game, rng)
 }
-fn syntetic_river(lock_cards: &Vec<Card>, spr: Decimal) -> ConfigPostflop {
-    let preflop_game = syntetic_preflop(lock_cards);
-    let flop_game = syntetic_postflop(&preflop_game);
-    let turn_game = syntetic_postflop(&flop_game);
-    let mut river_game = syntetic_postflop(&turn_game);
+fn syntetic_river(lock_cards: &Vec<Card>, spr: Decimal, rng: &mut GameRng) -> ConfigPostflop {
+    let preflop_game = syntetic_preflop(lock_cards, rng);
+    let flop_game = syntetic_postflop(&preflop_game, rng);
+    let turn_game = syntetic_postflop(&flop_game, rng);
+    let mut river_game = syntetic_postflop(&turn_game, rng);
 
     let fake_board = Utils::new_fake_flop_board(&river_game);
     let prev_fake_board = Utils::new_fake_flop_board(&turn_game);
     // let ch_board_str = fake_board != prev_fake_board;
     let ch_board_str = cacl_change_board(fake_board, prev_fake_board);
 
-    let prev_agr_pose = modify_game_ml(&mut river_game, spr);
+    let prev_agr_pose = modify_game_ml(&mut river_game, spr, rng);
     ConfigPostflop {
         game: river_game,
         ch_board_str,
@@ -1367,9 +836,11 @@ fn temporary_modify_river(init_game: &mut PostflopGame, position_real_player: &P
     )
     .unwrap();
 }
-fn modify_game_ml(init_game: &mut PostflopGame, spr: Decimal) -> Option<Position> {
-    let mut rnd = rand::thread_rng();
-
+fn modify_game_ml(
+    init_game: &mut PostflopGame,
+    spr: Decimal,
+    rnd: &mut GameRng,
+) -> Option<Position> {
     /* Сгенерирую сфолдвшие позиции.
     100% - ХА
     20% - 3-вей
@@ -1443,17 +914,17 @@ fn modify_game_ml(init_game: &mut PostflopGame, spr: Decimal) -> Option<Position
         _ => unreachable!(),
     }
 }
-fn rnd_one_positions_not_folded(init_game: &impl Game) -> Position {
+fn rnd_one_positions_not_folded(init_game: &impl Game, rng: &mut GameRng) -> Position {
     loop {
-        let pose = Position::rnd_position();
+        let pose = Position::rnd_position_with(rng);
         if !init_game.folded_positions().contains(&pose) {
             return pose;
         }
     }
 }
-fn gen_serde_games_river() -> HashMap<String, Vec<(FakePostflopNew, Position, ReadyHand)>> {
-    let mut rnd = rand::thread_rng();
-
+fn gen_serde_games_river(
+    mut rnd: GameRng,
+) -> HashMap<String, Vec<(FakePostflopNew, Position, ReadyHand)>> {
     let mut fakes_count = HashMap::new();
     let mut serde_river = HashMap::new();
     let mut fakes = HashSet::new();
@@ -1476,15 +947,16 @@ fn gen_serde_games_river() -> HashMap<String, Vec<(FakePostflopNew, Position, Re
             2 => dec!(20),
             _ => unreachable!(),
         };
-        let config = syntetic_river(&lock_cards, spr);
+        let config = syntetic_river(&lock_cards, spr, &mut rnd);
 
-        let mut river_game: PostflopGame = config.game;
+        // Канонические масти: игры, которые отличаются только мастями, пишутся один раз.
+        let mut river_game: PostflopGame = config.game.canonical();
         let prev_agr_pose = config.prev_agr_pose;
         let ch_board_str = config.ch_board_str;
 
         let specific_board = true;
 
-        let real_player_hand = Hand::rnd_hand(&river_game.cards);
+        let real_player_hand = Hand::rnd_hand_with(&river_game.cards, &mut rnd);
 
         // Tuple for serde
         let mut tuples = vec![];
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+use crate::{eval_hand::showdown_strength, Card, GameVariant, Hand, HandStrength, ReadyHand};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NutHolding {
+    pub strength: HandStrength,
+    pub ready_hand: ReadyHand,
+    pub holdings: Vec<(Card, Card)>, // Две карты из руки, которые дают эту силу.
+}
+
+/*
+Все возможные руки на борде от натса вниз. Каждая рука омахи играет ровно 2 карты из руки и 3 с
+борда, поэтому достаточно перебрать все пары неизвестных карт: лучшая рука из 4-5 карт это лучшая
+из ее пар (в холдеме лучшие 5 из 7). Одинаковые по силе пары собираются в одну NutHolding.
+*/
+pub fn nut_rank(variant: GameVariant, board: &[Card]) -> Result<Vec<NutHolding>, String> {
+    check_board(board)?;
+    let mut by_strength: BTreeMap<HandStrength, Vec<(Card, Card)>> = BTreeMap::new();
+    for (strength, holding) in possible_holdings(variant, board, &[]) {
+        by_strength.entry(strength).or_default().push(holding);
+    }
+    Ok(by_strength
+        .into_iter()
+        .rev()
+        .map(|(strength, holdings)| NutHolding {
+            strength,
+            ready_hand: ReadyHand::from(strength),
+            holdings,
+        })
+        .collect())
+}
+
+// Место руки среди рук, которые могут быть у соперника (1 - натс, 2 - второй натс...).
+// Карты руки блокируют: пара соперника не может содержать наши карты.
+pub fn hand_rank_among_possible(
+    variant: GameVariant,
+    hand: &Hand,
+    board: &[Card],
+) -> Result<usize, String> {
+    check_hand(variant, hand, board)?;
+    let strength = showdown_strength(variant, hand, board);
+    let mut better = possible_holdings(variant, board, &hand.cards)
+        .into_iter()
+        .map(|(other, _)| other)
+        .filter(|&other| other > strength)
+        .collect::<Vec<_>>();
+    better.sort_unstable();
+    better.dedup();
+    Ok(better.len() + 1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blocked {
+    pub ready_hand: ReadyHand,
+    pub combos: usize, // Пар карт, которые дают эту силу (наши карты тоже считаются).
+    pub blocked: usize, // Из них с нашими картами.
+}
+impl Blocked {
+    pub fn share(&self) -> f64 {
+        if self.combos == 0 {
+            0.0
+        } else {
+            self.blocked as f64 / self.combos as f64
+        }
+    }
+}
+
+/*
+Блокеры руки на борде вместо одного флага Utils::we_have_blockers. Комбо считаются парами карт
+(ровно две карты из руки играют), поэтому спаренные борда (каре, фулл-хаузы) и сеты учитываются
+так же, как флеши и стриты.
+- nuts, second_nuts: сколько комбо натса и второго натса содержат наши карты.
+- value_blocked: доля комбо, которые нас бьют, с нашими картами. Нас никто не бьет - 0.
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockerReport {
+    pub nuts: Blocked,
+    pub second_nuts: Option<Blocked>,
+    pub value_blocked: f64,
+}
+pub fn blocker_report(
+    variant: GameVariant,
+    hand: &Hand,
+    board: &[Card],
+) -> Result<BlockerReport, String> {
+    check_hand(variant, hand, board)?;
+    let strength = showdown_strength(variant, hand, board);
+    let holdings = possible_holdings(variant, board, &[]);
+    let is_blocked =
+        |(first, second): &(Card, Card)| hand.cards.contains(first) || hand.cards.contains(second);
+    let mut strengths = holdings.iter().map(|(s, _)| *s).collect::<Vec<_>>();
+    strengths.sort_unstable_by(|a, b| b.cmp(a));
+    strengths.dedup();
+    let blocked_for = |target: HandStrength| {
+        let combos = holdings.iter().filter(|(s, _)| *s == target);
+        Blocked {
+            ready_hand: ReadyHand::from(target),
+            combos: combos.clone().count(),
+            blocked: combos.filter(|(_, holding)| is_blocked(holding)).count(),
+        }
+    };
+
+    let better = holdings
+        .iter()
+        .filter(|(s, _)| *s > strength)
+        .collect::<Vec<_>>();
+    let value_blocked = if better.is_empty() {
+        0.0
+    } else {
+        better
+            .iter()
+            .filter(|(_, holding)| is_blocked(holding))
+            .count() as f64
+            / better.len() as f64
+    };
+    Ok(BlockerReport {
+        nuts: blocked_for(strengths[0]),
+        second_nuts: strengths.get(1).map(|&s| blocked_for(s)),
+        value_blocked,
+    })
+}
+
+// Сила каждой пары карт, кроме карт борда и dead_cards.
+pub(crate) fn possible_holdings(
+    variant: GameVariant,
+    board: &[Card],
+    dead_cards: &[Card],
+) -> Vec<(HandStrength, (Card, Card))> {
+    let left = Card::deck()
+        .into_iter()
+        .filter(|card| !board.contains(card) && !dead_cards.contains(card))
+        .collect::<Vec<Card>>();
+    let mut holdings = Vec::with_capacity(left.len() * left.len() / 2);
+    for (i, &first) in left.iter().enumerate() {
+        for &second in left[i + 1..].iter() {
+            let hand = Hand::from_cards(vec![first, second]).unwrap_or_else(|_| unreachable!());
+            let strength = showdown_strength(variant, &hand, board);
+            holdings.push((strength, (hand.cards[0], hand.cards[1])));
+        }
+    }
+    holdings
+}
+
+fn check_board(board: &[Card]) -> Result<(), String> {
+    if !(3..=5).contains(&board.len()) {
+        return Err(format!(
+            "Error: board must have from 3 to 5 cards, got {}",
+            board.len()
+        ));
+    }
+    Ok(())
+}
+fn check_hand(variant: GameVariant, hand: &Hand, board: &[Card]) -> Result<(), String> {
+    check_board(board)?;
+    if hand.cards.len() != variant.hole_cards() {
+        return Err(format!(
+            "Error: hand must have {} cards",
+            variant.hole_cards()
+        ));
+    }
+    if board.iter().any(|card| hand.cards.contains(card)) {
+        return Err(String::from("Error: board card is in hand"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{cards, hand};
+    use crate::Rank;
+
+    #[test]
+    fn nuts_list_and_blockers() {
+        let board = cards("As Ks Qs 7d 2c");
+        let nuts = nut_rank(GameVariant::Plo4, &board).unwrap();
+        assert_eq!(nuts[0].ready_hand, ReadyHand::FlashRoal);
+        let js_ts = cards("Js Ts");
+        assert_eq!(nuts[0].holdings, vec![(js_ts[0], js_ts[1])]);
+        assert!(matches!(nuts[1].ready_hand, ReadyHand::Flash(..)));
+        assert_eq!(
+            nuts.iter().map(|n| n.holdings.len()).sum::<usize>(),
+            47 * 46 / 2
+        );
+
+        assert_eq!(
+            hand_rank_among_possible(GameVariant::Plo4, &hand("Js Ts 3h 3d"), &board),
+            Ok(1)
+        );
+        // Со Js в руке рояля у соперника быть не может: флеш с J это натс.
+        assert_eq!(
+            hand_rank_among_possible(GameVariant::Plo4, &hand("Js 9s 4h 4d"), &board),
+            Ok(1)
+        );
+        // Без Js: сильнее флеши Js 8s, Js 7s ... Js 2s, рояля нет из-за Ts в руке.
+        assert_eq!(
+            hand_rank_among_possible(GameVariant::Plo4, &hand("Ts 9s 4h 4d"), &board),
+            Ok(8)
+        );
+        assert!(hand_rank_among_possible(GameVariant::Plo4, &hand("As 9s 4h 4d"), &board).is_err());
+        assert!(nut_rank(GameVariant::Plo4, &board[..2]).is_err());
+    }
+    #[test]
+    fn plo5_and_holdem_ranks() {
+        let board = cards("As Ks Qs 7d 2c");
+        let plo5 = hand("Js Ts 3h 3d 4c");
+        assert_eq!(
+            hand_rank_among_possible(GameVariant::Plo5, &plo5, &board),
+            Ok(1)
+        );
+        assert!(hand_rank_among_possible(GameVariant::Plo4, &plo5, &board).is_err());
+        let report = blocker_report(GameVariant::Plo5, &plo5, &board).unwrap();
+        assert_eq!((report.nuts.combos, report.nuts.blocked), (1, 1));
+
+        // В холдеме хватает одной червы: Th с 9h 8h 7h 6h это натс, в омахе нет.
+        let board = cards("9h 8h 7h 6h 2c");
+        assert_eq!(
+            hand_rank_among_possible(GameVariant::Holdem, &hand("Th 2d"), &board),
+            Ok(1)
+        );
+        assert!(
+            hand_rank_among_possible(GameVariant::Plo4, &hand("Th 2d 3s 4s"), &board).unwrap() > 1
+        );
+        let nuts = nut_rank(GameVariant::Holdem, &board).unwrap();
+        assert!(matches!(
+            nuts[0].ready_hand,
+            ReadyHand::StreetFlash(Rank::Jack)
+        ));
+    }
+    #[test]
+    fn blockers_on_flush_and_paired_boards() {
+        let board = cards("As Ks Qs 7d 2c");
+        let report = blocker_report(GameVariant::Plo4, &hand("Js 9s 4h 4d"), &board).unwrap();
+        assert_eq!(report.nuts.ready_hand, ReadyHand::FlashRoal);
+        assert_eq!((report.nuts.combos, report.nuts.blocked), (1, 1));
+        let second = report.second_nuts.unwrap();
+        assert_eq!((second.combos, second.blocked), (1, 1));
+        // Бьет нас только рояль, и он целиком заблокирован.
+        assert_eq!(report.value_blocked, 1.0);
+        let nuts = blocker_report(GameVariant::Plo4, &hand("Js Ts 4h 4d"), &board).unwrap();
+        assert_eq!(nuts.value_blocked, 0.0);
+
+        // Спаренный борд: натс каре королей (Kc Ks), второй натс каре семерок (7d 7h).
+        let board = cards("Kd Kh 7c 7s 2d");
+        let report = blocker_report(GameVariant::Plo4, &hand("Kc Qc Jd Th"), &board).unwrap();
+        assert_eq!(report.nuts.ready_hand, ReadyHand::Care(Rank::King));
+        assert_eq!(report.nuts.share(), 1.0);
+        let second = report.second_nuts.unwrap();
+        assert_eq!(second.ready_hand, ReadyHand::Care(Rank::Seven));
+        assert_eq!(second.share(), 0.0);
+        assert!(report.value_blocked > 0.0 && report.value_blocked < 1.0);
+
+        let no_blockers = blocker_report(GameVariant::Plo4, &hand("9c 8c 4h 3h"), &board).unwrap();
+        assert_eq!(no_blockers.nuts.blocked, 0);
+        assert!(no_blockers.value_blocked < report.value_blocked);
+    }
+}
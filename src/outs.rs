@@ -0,0 +1,132 @@
+use crate::{
+    eval_hand::showdown_strength, nuts::possible_holdings, Card, GameVariant, Hand, ReadyHand,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Out {
+    pub card: Card,
+    pub ready_hand: ReadyHand, // Комбинация после этой карты.
+    pub improves: bool,        // Комбинация стала старше по виду (пара -> две пары и т.д.).
+    pub is_nuts: bool,         // Ни одна рука соперника не бьет нас на новом борде.
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outs {
+    pub current: ReadyHand,
+    pub cards: Vec<Out>, // Все оставшиеся карты колоды по порядку Card::deck.
+}
+impl Outs {
+    pub fn outs(&self) -> impl Iterator<Item = &Out> {
+        self.cards.iter().filter(|out| out.improves)
+    }
+    // Чистые ауты: улучшают до натса.
+    pub fn clean(&self) -> usize {
+        self.outs().filter(|out| out.is_nuts).count()
+    }
+    // Грязные ауты: улучшают, но на новом борде есть рука сильнее.
+    pub fn dirty(&self) -> usize {
+        self.outs().filter(|out| !out.is_nuts).count()
+    }
+}
+
+/*
+Ауты руки на флопе или терне: для каждой оставшейся карты - какая будет комбинация.
+- Неизвестные карты это колода без руки и борда. Рук соперников не знаем, натс считается по всем
+  двум картам из неизвестных (в омахе 2 + 3 с борда, в холдеме лучшие 5 из 7).
+- Аут - карта, после которой вид комбинации старше текущего.
+*/
+pub fn outs(variant: GameVariant, hand: &Hand, board: &[Card]) -> Result<Outs, String> {
+    if ![3, 4].contains(&board.len()) {
+        return Err(format!(
+            "Error: outs need 3 or 4 board cards, got {}",
+            board.len()
+        ));
+    }
+    if hand.cards.len() != variant.hole_cards() {
+        return Err(format!(
+            "Error: hand must have {} cards",
+            variant.hole_cards()
+        ));
+    }
+    if board.iter().any(|card| hand.cards.contains(card)) {
+        return Err(String::from("Error: board card is in hand"));
+    }
+    let current = ReadyHand::from(showdown_strength(variant, hand, board));
+    let unknown = Card::deck()
+        .into_iter()
+        .filter(|card| !hand.cards.contains(card) && !board.contains(card))
+        .collect::<Vec<Card>>();
+
+    let mut new_board = board.to_vec();
+    let cards = unknown
+        .iter()
+        .map(|&card| {
+            new_board.truncate(board.len());
+            new_board.push(card);
+            let strength = showdown_strength(variant, hand, &new_board);
+            let ready_hand = ReadyHand::from(strength);
+            Out {
+                card,
+                ready_hand,
+                improves: kind(ready_hand) > kind(current),
+                is_nuts: possible_holdings(variant, &new_board, &hand.cards)
+                    .into_iter()
+                    .all(|(other, _)| strength >= other),
+            }
+        })
+        .collect();
+    Ok(Outs { current, cards })
+}
+
+// Вид комбинации от старшей карты (0) до флеш-рояля (9).
+fn kind(ready_hand: ReadyHand) -> u8 {
+    match ready_hand {
+        ReadyHand::HightCards(..) => 0,
+        ReadyHand::OnePair { .. } => 1,
+        ReadyHand::TwoPair { .. } => 2,
+        ReadyHand::Trips { .. } => 3,
+        ReadyHand::Street(_) => 4,
+        ReadyHand::Flash(..) => 5,
+        ReadyHand::FullHouse { .. } => 6,
+        ReadyHand::Care(_) => 7,
+        ReadyHand::StreetFlash(_) => 8,
+        ReadyHand::FlashRoal => 9,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{cards, hand};
+    use crate::Rank;
+
+    fn out(outs: &Outs, card: &str) -> Out {
+        let card = card.parse::<Card>().unwrap();
+        *outs.cards.iter().find(|out| out.card == card).unwrap()
+    }
+    #[test]
+    fn flush_and_street_outs_on_flop() {
+        let hand = hand("As Ks 7d 2c");
+        let board = cards("Qs Js 3h");
+        let outs = outs(GameVariant::Plo4, &hand, &board).unwrap();
+        assert_eq!(outs.cards.len(), 45);
+        assert!(matches!(outs.current, ReadyHand::HightCards(..)));
+
+        assert_eq!(out(&outs, "Ts").ready_hand, ReadyHand::FlashRoal);
+        assert!(out(&outs, "Ts").is_nuts);
+        assert_eq!(out(&outs, "Tc").ready_hand, ReadyHand::Street(Rank::Ace));
+        assert!(out(&outs, "Tc").is_nuts);
+        // Флеш на спаренном борде и когда возможен стрит-флеш - грязные ауты.
+        assert!(out(&outs, "3s").improves && !out(&outs, "3s").is_nuts);
+        assert!(!out(&outs, "9s").is_nuts);
+        assert!(out(&outs, "6s").is_nuts);
+        assert!(!out(&outs, "Kh").is_nuts);
+        assert!(!out(&outs, "4d").improves);
+        // Чистые: 4 десятки и 2s, 4s, 5s, 6s, 7s.
+        assert_eq!(outs.clean(), 9);
+        assert_eq!(outs.clean() + outs.dirty(), outs.outs().count());
+
+        assert!(super::outs(GameVariant::Plo4, &hand, &board[..2]).is_err());
+        assert!(super::outs(GameVariant::Plo5, &hand, &board).is_err());
+    }
+}
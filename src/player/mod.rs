@@ -37,9 +37,13 @@ impl FakeStackSize {
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub enum Position {
     // Order like postflop, because I cmp it in fakepostflop. Was from UTG
+    // Utg1, Utg2, Lj only at full ring tables (see TableConfig), 6-max is Sb, Bb, Utg, Mp, Co, Btn.
     Sb,
     Bb,
     Utg,
+    Utg1,
+    Utg2,
+    Lj,
     Mp,
     Co,
     Btn,
@@ -61,6 +65,9 @@ impl Position {
     pub fn rnd_position() -> Self {
         rand::random()
     }
+    pub fn rnd_position_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        rng.gen()
+    }
     pub fn rnd_two_positions() -> Vec<Self> {
         let positions = vec![Self::rnd_position()];
         // while positions.len() == 1 {
@@ -71,6 +78,7 @@ impl Position {
         // }
         positions
     }
+    // Позиции 6-max стола в порядке постфлопа. Для других столов TableConfig::postflop_order.
     pub fn all_poses() -> Vec<Self> {
         vec![
             Position::Sb,
@@ -100,10 +108,18 @@ impl Debug for Player {
 }
 impl Player {
     pub fn rnd_player(position: Position, dead_cards: &Vec<Card>) -> Self {
+        Self::rnd_player_with(position, 4, dead_cards, &mut rand::thread_rng())
+    }
+    pub fn rnd_player_with<R: Rng + ?Sized>(
+        position: Position,
+        hole_cards: usize,
+        dead_cards: &Vec<Card>,
+        rng: &mut R,
+    ) -> Self {
         Self {
             position,
-            stack_size: Decimal::new(rand::thread_rng().gen_range(30..=250), 0),
-            hand: Hand::rnd_hand(dead_cards),
+            stack_size: Decimal::new(rng.gen_range(30..=250), 0),
+            hand: Hand::rnd_hand_of_size_with(hole_cards, dead_cards, rng),
         }
     }
 }
@@ -20,13 +20,15 @@ impl ConfigBoard {
 
 pub fn fake_comb_side_ready(
     hand: &Hand,
-    ready_hand: ReadyHand,
+    ready_hand: impl Into<ReadyHand>,
     board: &Vec<Card>, // Always sort from top to low!
 ) -> FakePostReadyHand {
     /* Так как некоторые категории пересекаются, то для производительности и чтобы убрать лишние
     проверки и тд, в логике важен порядок групп. От сильнейших к слабейшим.
     Наиболее явный пример, см. фулл-хауз или стрит или варианты трипсо-сетов
-    ВАЖНО board - всегда должен быть отсортирован от большего ранга к меньшему*/
+    ВАЖНО board - всегда должен быть отсортирован от большего ранга к меньшему
+    ready_hand - ReadyHand или числовая сила HandStrength. */
+    let ready_hand = ready_hand.into();
     let ranks = board.iter().map(|c| c.rank).collect::<Vec<Rank>>();
     let mut map = HashMap::new();
     ranks.iter().for_each(|&rank| {
@@ -77,7 +79,12 @@ pub fn fake_comb_side_ready(
     }
     FakePostReadyHand::Nothing
 }
-pub fn fake_comb_side_fd(hand: &Hand, ready_hand: ReadyHand, board: &Vec<Card>) -> FakePostflopFD {
+pub fn fake_comb_side_fd(
+    hand: &Hand,
+    ready_hand: impl Into<ReadyHand>,
+    board: &Vec<Card>,
+) -> FakePostflopFD {
+    let ready_hand = ready_hand.into();
     // Если у нас флеш, то пофигу на флешдро - не нужно считать время и не нужно плодить разные фейки.
     if let ReadyHand::Flash(_, _, _, _, _) = ready_hand {
         return FakePostflopFD::Nothing;
@@ -180,7 +187,12 @@ pub fn fake_comb_side_fd(hand: &Hand, ready_hand: ReadyHand, board: &Vec<Card>)
         FakePostflopFD::Nothing
     }
 }
-pub fn fake_comb_side_sd(hand: &Hand, ready_hand: ReadyHand, board: &Vec<Card>) -> FakePostflopSD {
+pub fn fake_comb_side_sd(
+    hand: &Hand,
+    ready_hand: impl Into<ReadyHand>,
+    board: &Vec<Card>,
+) -> FakePostflopSD {
+    let ready_hand = ready_hand.into();
     if let ReadyHand::Street(_) = ready_hand {
         return FakePostflopSD::Nothing;
     }
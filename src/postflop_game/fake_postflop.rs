@@ -695,16 +695,16 @@ impl Utils {
     fn ip_cmp(game: &PostflopGame, position: Position) -> bool {
         /* Логика:
         Берем только позиции, которые не в фолде, включая текущую.
-        И из оставшихся все должны быть не больше текущей.
+        И из оставшихся текущая должна ходить последней по порядку постфлопа стола.
          */
-        let ordered_list_by_action = Self::_position_order_list();
+        let ordered_list_by_action = game.table.postflop_order();
         ordered_list_by_action
             .iter()
-            .filter(|&pos| !game.folded_positions.contains(pos))
-            .all(|&pos| pos <= position)
+            .rfind(|&pos| !game.folded_positions.contains(pos))
+            == Some(&position)
     }
     fn hu(game: &PostflopGame) -> bool {
-        game.folded_positions.len() >= 4
+        game.folded_positions.len() + 2 >= game.table.seats() as usize
     }
     /* Эта реализация очень классная и точна для рассчета базы солверных решений.
     Но когда я буду это же рассчитывать после распознавания картинки, то мне очень не нравится,
@@ -817,15 +817,13 @@ impl Utils {
                 biggest_postflop_stack_except_my = curr_player.stack_size
             }
         }
+        let pot = game.winnable_pot(position);
         if biggest_postflop_stack_except_my > my_initial_stack {
-            FakeSpr::from(
-                my_initial_stack - my_money_in_pot_already,
-                game.main_pot.value,
-            )
+            FakeSpr::from(my_initial_stack - my_money_in_pot_already, pot)
         } else {
             FakeSpr::from(
                 biggest_postflop_stack_except_my - my_money_in_pot_already,
-                game.main_pot.value,
+                pot,
             )
         }
     }
@@ -864,19 +862,9 @@ impl Utils {
         } else {
             add_to_commit = player.stack_size - my_commit;
         }
-        let ratio = dec!(100) * add_to_commit / game.main_pot.value;
+        let ratio = dec!(100) * add_to_commit / game.winnable_pot(position);
         RatioNeedCoomitToPotPercent::from(ratio)
     }
-    fn _position_order_list() -> Vec<Position> {
-        vec![
-            Position::Sb,
-            Position::Bb,
-            Position::Utg,
-            Position::Mp,
-            Position::Co,
-            Position::Btn,
-        ]
-    }
     fn potential_fe(
         player: &Player,
         fake_board: &FakeBoardNew,
@@ -1,4 +1,6 @@
-use crate::{Card, Game, Player, Position, Pot, Rank, MAP_INLINE_RANKS_RIVER};
+use crate::hand::SuitMap;
+use crate::{Card, Game, Player, Position, Pot, Rank, TableConfig, MAP_INLINE_RANKS_RIVER};
+use rand::Rng;
 use rust_decimal::prelude::*;
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
@@ -18,6 +20,9 @@ pub struct PostflopGame {
     pub main_pot: Pot,
     pub min_bet: Decimal,
     pub dead_cards: Vec<Card>,
+    // Старые сериализованные игры (river_fake_and_game.txt) все 6-max.
+    #[serde(default)]
+    pub table: TableConfig,
 }
 impl Debug for PostflopGame {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -69,46 +74,87 @@ impl Game for PostflopGame {
     fn is_preflop_game(&self) -> bool {
         false
     }
+    fn table(&self) -> TableConfig {
+        self.table
+    }
 }
 impl PostflopGame {
     pub fn from(init_game: &impl Game) -> Self {
-        let postflop_players = PostflopGame::recalc_player_stacks(init_game, &init_game.players());
+        Self::from_with_rng(init_game, &mut rand::thread_rng())
+    }
+    pub fn from_with_rng<R: Rng + ?Sized>(init_game: &impl Game, rng: &mut R) -> Self {
+        let mut new_dead_cards = init_game.dead_cards().clone();
+        let cards = Self::rnd_board_and_modify_deadcards_with(init_game, &mut new_dead_cards, rng);
+        Self::from_board(init_game, cards, new_dead_cards)
+    }
+    pub fn from_with_new_cards(init_game: &impl Game, new_cards: &[Card]) -> Result<Self, String> {
+        // Следующая улица с заранее известными картами (реплей, воспроизведение раздачи).
+        let size_gen = if init_game.is_preflop_game() { 3 } else { 1 };
+        if new_cards.len() != size_gen {
+            return Err(format!(
+                "Error: need {} new board cards, got {}",
+                size_gen,
+                new_cards.len()
+            ));
+        }
+        if new_cards.iter().any(|c| init_game.dead_cards().contains(c)) {
+            return Err(String::from("Error: new board card is already dead!"));
+        }
         let mut new_dead_cards = init_game.dead_cards().clone();
-        Self {
-            cards: Self::rnd_board_and_modify_deadcards(init_game, &mut new_dead_cards),
+        new_dead_cards.extend_from_slice(new_cards);
+        let mut cards = new_cards.to_vec();
+        if let Some(v) = init_game.cards() {
+            cards.extend_from_slice(v)
+        }
+        let cards = PostflopGame::new_sorted_board_cards(cards)?;
+        Ok(Self::from_board(init_game, cards, new_dead_cards))
+    }
+    fn from_board(init_game: &impl Game, cards: Vec<Card>, dead_cards: Vec<Card>) -> Self {
+        let postflop_players = PostflopGame::recalc_player_stacks(init_game, &init_game.players());
+        let mut game = Self {
+            cards,
             players: postflop_players,
-            positions_and_money: HashMap::from([
-                (Position::Utg, dec!(0)),
-                (Position::Mp, dec!(0)),
-                (Position::Co, dec!(0)),
-                (Position::Btn, dec!(0)),
-                (Position::Sb, dec!(0)),
-                (Position::Bb, dec!(0)),
-            ]),
+            positions_and_money: init_game
+                .table()
+                .positions()
+                .into_iter()
+                .map(|pos| (pos, dec!(0)))
+                .collect(),
             folded_positions: init_game.folded_positions().clone(),
             main_pot: {
                 let mut pot = init_game.main_pot().clone();
                 pot.prev_street_end_size = pot.value;
+                pot.prev_streets_commits = init_game.total_commits();
                 pot
             },
-            min_bet: dec!(1),
-            dead_cards: new_dead_cards,
-        }
+            min_bet: init_game.table().blinds().bb,
+            dead_cards,
+            table: init_game.table(),
+        };
+        game.recalculate_side_pots();
+        game
     }
     pub fn rnd_board_and_modify_deadcards(
         game: &impl Game,
         dead_cards: &mut Vec<Card>,
+    ) -> Vec<Card> {
+        Self::rnd_board_and_modify_deadcards_with(game, dead_cards, &mut rand::thread_rng())
+    }
+    pub fn rnd_board_and_modify_deadcards_with<R: Rng + ?Sized>(
+        game: &impl Game,
+        dead_cards: &mut Vec<Card>,
+        rng: &mut R,
     ) -> Vec<Card> {
         let size_gen = if game.is_preflop_game() { 3 } else { 1 };
-        let mut set = HashSet::with_capacity(size_gen);
-        while set.len() < size_gen {
-            let card = Card::rnd_card();
-            if dead_cards.contains(&card) {
+        // Vec, а не HashSet: порядок dead_cards должен повторяться при том же сиде.
+        let mut cards = Vec::with_capacity(size_gen);
+        while cards.len() < size_gen {
+            let card = Card::rnd_card_with(rng);
+            if dead_cards.contains(&card) || cards.contains(&card) {
                 continue;
             }
-            set.insert(card);
+            cards.push(card);
         }
-        let mut cards = set.into_iter().collect::<Vec<Card>>();
         dead_cards.extend_from_slice(&cards);
         if let Some(v) = game.cards() {
             cards.extend_from_slice(v)
@@ -127,6 +173,24 @@ impl PostflopGame {
             ))
         }
     }
+    // Та же игра с каноническими мастями (hand::SuitMap::canonical по борду и рукам игроков).
+    // Игры, которые отличаются только мастями, после этого совпадают.
+    pub fn canonical(&self) -> Self {
+        let hands = self
+            .players
+            .iter()
+            .map(|player| &player.hand)
+            .collect::<Vec<_>>();
+        let map = SuitMap::canonical(&self.cards, &hands);
+        let mut game = self.clone();
+        game.cards = PostflopGame::new_sorted_board_cards(map.cards(&self.cards))
+            .unwrap_or_else(|_| unreachable!());
+        game.dead_cards = map.cards(&self.dead_cards);
+        for player in game.players.iter_mut() {
+            player.hand = map.hand(&player.hand);
+        }
+        game
+    }
     pub fn player_by_position_as_ref(&self, position: Position) -> &Player {
         self.players
             .iter()
@@ -704,3 +704,41 @@ pub mod flash_blockers {
         assert_eq!(flash_blocker, answer);
     }
 }
+#[cfg(test)]
+pub mod canonical {
+    use crate::{seeded_rng, Card, Game, PostflopGame, PreflopGame, Suit};
+
+    #[test]
+    fn same_game_with_other_suits() {
+        let preflop_game = PreflopGame::new_with_rng(&mut seeded_rng(5));
+        let flop_game = PostflopGame::from_with_rng(&preflop_game, &mut seeded_rng(6));
+        let turn_game = PostflopGame::from_with_rng(&flop_game, &mut seeded_rng(7));
+        let river_game = PostflopGame::from_with_rng(&turn_game, &mut seeded_rng(8));
+
+        // Меняем местами пики и трефы во всех картах.
+        let swap = |card: &Card| {
+            let suit = match card.suit {
+                Suit::Spades => Suit::Clubs,
+                Suit::Clubs => Suit::Spades,
+                suit => suit,
+            };
+            Card::new(card.rank, suit)
+        };
+        let mut swapped = river_game.clone();
+        swapped.cards =
+            PostflopGame::new_sorted_board_cards(river_game.cards.iter().map(swap).collect())
+                .unwrap();
+        swapped.dead_cards = river_game.dead_cards.iter().map(swap).collect();
+        for player in swapped.players.iter_mut() {
+            player.hand =
+                crate::Hand::from_cards(player.hand.cards.iter().map(swap).collect()).unwrap();
+        }
+        let canonical = river_game.canonical();
+        let swapped = swapped.canonical();
+        assert_eq!(canonical.cards, swapped.cards);
+        assert_eq!(canonical.dead_cards(), swapped.dead_cards());
+        for (a, b) in canonical.players.iter().zip(swapped.players.iter()) {
+            assert_eq!(a.hand, b.hand);
+        }
+    }
+}
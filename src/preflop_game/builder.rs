@@ -0,0 +1,173 @@
+use rand::Rng;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+
+use crate::{Card, Hand, Player, Position, PreflopGame, TableConfig};
+
+struct Seat {
+    position: Position,
+    stack_size: Decimal,
+    hand: Option<Hand>,
+}
+
+/*
+Сборка префлопа из реального стола: у каждого места свой стек, карманные карты можно зафиксировать.
+- Каждая позиция стола должна быть заполнена ровно один раз, чужих позиций быть не должно.
+- Стеки строго больше нуля (до анте и блайндов).
+- Все зафиксированные карты и lock_cards уникальны, в руке столько карт, сколько в варианте игры.
+Остальным местам руки раздаются случайно.
+*/
+pub struct PreflopGameBuilder {
+    table: TableConfig,
+    seats: Vec<Seat>,
+    lock_cards: Vec<Card>,
+}
+impl PreflopGameBuilder {
+    pub fn new(table: TableConfig) -> Self {
+        Self {
+            table,
+            seats: vec![],
+            lock_cards: vec![],
+        }
+    }
+    pub fn seat(mut self, position: Position, stack_size: Decimal) -> Self {
+        self.seats.push(Seat {
+            position,
+            stack_size,
+            hand: None,
+        });
+        self
+    }
+    pub fn seat_with_hand(mut self, position: Position, stack_size: Decimal, hand: Hand) -> Self {
+        self.seats.push(Seat {
+            position,
+            stack_size,
+            hand: Some(hand),
+        });
+        self
+    }
+    pub fn lock_cards(mut self, cards: &[Card]) -> Self {
+        self.lock_cards.extend_from_slice(cards);
+        self
+    }
+    pub fn build(self) -> Result<PreflopGame, String> {
+        self.build_with_rng(&mut rand::thread_rng())
+    }
+    pub fn build_with_rng<R: Rng + ?Sized>(self, rng: &mut R) -> Result<PreflopGame, String> {
+        let positions = self.table.positions();
+        let mut seated = HashSet::new();
+        for seat in self.seats.iter() {
+            if !positions.contains(&seat.position) {
+                return Err(format!(
+                    "Error: no {:?} at {} seats table",
+                    seat.position,
+                    self.table.seats()
+                ));
+            }
+            if !seated.insert(seat.position) {
+                return Err(format!("Error: {:?} seated twice", seat.position));
+            }
+            if seat.stack_size <= Decimal::ZERO {
+                return Err(format!("Error: {:?} stack must be positive", seat.position));
+            }
+        }
+        if let Some(pos) = positions.iter().find(|pos| !seated.contains(pos)) {
+            return Err(format!("Error: {:?} is empty", pos));
+        }
+
+        let hole_cards = self.table.variant().hole_cards();
+        if let Some(seat) = self.seats.iter().find(|seat| {
+            seat.hand
+                .as_ref()
+                .is_some_and(|h| h.cards.len() != hole_cards)
+        }) {
+            return Err(format!(
+                "Error: {:?} hand must have {} cards",
+                seat.position, hole_cards
+            ));
+        }
+        let mut dead_cards = self.lock_cards.clone();
+        let fixed_cards = self
+            .seats
+            .iter()
+            .filter_map(|seat| seat.hand.as_ref())
+            .flat_map(|hand| hand.cards.iter().copied());
+        for card in fixed_cards {
+            if dead_cards.contains(&card) {
+                return Err(format!("Error: card {:?} is used twice", card));
+            }
+            dead_cards.push(card);
+        }
+        // Случайные руки раздаются в порядке префлопа, как в PreflopGame::new_with_table.
+        let mut players = vec![];
+        for position in self.table.preflop_order() {
+            let seat = self
+                .seats
+                .iter()
+                .find(|seat| seat.position == position)
+                .unwrap_or_else(|| unreachable!());
+            let hand = match &seat.hand {
+                Some(hand) => hand.clone(),
+                None => {
+                    let hand = Hand::rnd_hand_of_size_with(hole_cards, &dead_cards, rng);
+                    dead_cards.extend_from_slice(&hand.cards);
+                    hand
+                }
+            };
+            players.push(Player {
+                position,
+                stack_size: seat.stack_size,
+                hand,
+            });
+        }
+        Ok(PreflopGame::from_players(self.table, players, dead_cards))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seeded_rng;
+    use crate::test_utils::hand;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn heads_up_with_uneven_stacks_and_fixed_hand() {
+        let my_hand = hand("As Ks Qh Jh");
+        let game = PreflopGameBuilder::new(TableConfig::heads_up())
+            .seat_with_hand(Position::Sb, dec!(40), my_hand.clone())
+            .seat(Position::Bb, dec!(250))
+            .build_with_rng(&mut seeded_rng(3))
+            .unwrap();
+        let sb = game.player_by_position_as_ref(Position::Sb);
+        assert_eq!(sb.hand, my_hand);
+        assert_eq!(sb.stack_size, dec!(40));
+        let bb = game.player_by_position_as_ref(Position::Bb);
+        assert_eq!(bb.stack_size, dec!(250));
+        assert!(bb.hand.cards.iter().all(|c| !my_hand.cards.contains(c)));
+        assert_eq!(game.dead_cards.len(), 8);
+        assert_eq!(game.main_pot.value, dec!(1.5));
+    }
+    #[test]
+    fn invalid_seats_rejected() {
+        let table = TableConfig::heads_up();
+        let same_card = hand("As Ks Qh Jh");
+        let other = hand("As 2s 3h 4h");
+        let cases = [
+            PreflopGameBuilder::new(table).seat(Position::Sb, dec!(100)),
+            PreflopGameBuilder::new(table)
+                .seat(Position::Sb, dec!(100))
+                .seat(Position::Bb, dec!(0)),
+            PreflopGameBuilder::new(table)
+                .seat(Position::Sb, dec!(100))
+                .seat(Position::Bb, dec!(100))
+                .seat(Position::Btn, dec!(100)),
+            PreflopGameBuilder::new(table)
+                .seat_with_hand(Position::Sb, dec!(100), same_card)
+                .seat_with_hand(Position::Bb, dec!(100), other),
+        ];
+        for builder in cases {
+            assert!(builder.build().is_err());
+        }
+    }
+}
@@ -42,7 +42,9 @@ pub enum FakePositionAction {
 impl FakePositionAction {
     pub fn from(position: Position) -> Self {
         match position {
-            Position::Utg | Position::Mp => Self::Early,
+            Position::Utg | Position::Utg1 | Position::Utg2 | Position::Lj | Position::Mp => {
+                Self::Early
+            }
             Position::Co | Position::Btn => Self::Late,
             Position::Bb => Self::BigB,
             Position::Sb => Self::SmallB,
@@ -145,7 +147,7 @@ impl Utils {
         game: &PreflopGame,
         position: Position,
     ) -> (FakeAction, FakePositionAction) {
-        let ordered_analize_pos = Self::position_order_list();
+        let ordered_analize_pos = game.table.postflop_order();
         let (mut max_value_pos, mut max_value) = (position, dec!(0));
         let mut i = 0u8; // Для позиционировании при обходе позиций, начиная с текущей.
         for &pos in ordered_analize_pos.iter().cycle() {
@@ -166,8 +168,10 @@ impl Utils {
                 max_value_pos = pos;
             }
         }
+        // Все фейки считаются в бб, чтобы не зависеть от размера блайндов.
+        let bb = game.table.blinds().bb;
         (
-            FakeAction::from(max_value),
+            FakeAction::from(max_value / bb),
             FakePositionAction::from(max_value_pos),
         )
     }
@@ -181,7 +185,7 @@ impl Utils {
     }
     fn ratio_add_commit_to_pot(game: &PreflopGame, position: Position) -> RatioNeedCoomitToPot {
         let my_commit = already_commit_by_pos(game, position);
-        RatioNeedCoomitToPot::from(my_commit)
+        RatioNeedCoomitToPot::from(my_commit / game.table.blinds().bb)
     }
     fn playing_stack(
         game: &PreflopGame,
@@ -193,7 +197,8 @@ impl Utils {
         - Else if we Deep then analize all start-street-stacks for all not-fold players exclude mine.
         - Else If exists at least one with >75 then Deep, except Shallow.
          */
-        if FakeStackSize::from(my_initial_stack) == FakeStackSize::Shallow {
+        let bb = game.table.blinds().bb;
+        if FakeStackSize::from(my_initial_stack / bb) == FakeStackSize::Shallow {
             return FakeStackSize::Shallow;
         }
         if !game
@@ -201,7 +206,7 @@ impl Utils {
             .iter()
             .filter(|player| {
                 player.position != position
-                    && FakeStackSize::from(player.stack_size) == FakeStackSize::Deep
+                    && FakeStackSize::from(player.stack_size / bb) == FakeStackSize::Deep
             })
             .all(|player| game.folded_positions().contains(&player.position))
         {
@@ -210,14 +215,4 @@ impl Utils {
             FakeStackSize::Shallow
         }
     }
-    fn position_order_list() -> Vec<Position> {
-        vec![
-            Position::Sb,
-            Position::Bb,
-            Position::Utg,
-            Position::Mp,
-            Position::Co,
-            Position::Btn,
-        ]
-    }
 }
@@ -1,5 +1,9 @@
+pub use builder::PreflopGameBuilder;
 pub use fake_preflop::*;
-pub use preflop::{ActionKind, Pot, PreflopGame};
+pub use preflop::{ActionKind, Pot, PreflopGame, SidePot};
+pub use ranking::{PreflopRank, PreflopRanking, PREFLOP_RANKING_FILE};
 
+pub mod builder;
 pub mod fake_preflop;
 pub mod preflop;
+pub mod ranking;
@@ -1,4 +1,4 @@
-use crate::{player, Card, Game, Hand, Player, Position};
+use crate::{player, Card, Game, Hand, Player, Position, TableConfig};
 use rand::Rng;
 use rust_decimal::prelude::*;
 use rust_decimal_macros::dec;
@@ -7,7 +7,7 @@ use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
 };
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum ActionKind {
     Fold,
     Call(Decimal),
@@ -30,10 +30,13 @@ impl Debug for ActionKind {
 }
 impl ActionKind {
     pub fn rnd_action_from(acts: &Vec<Self>) -> Option<Self> {
+        Self::rnd_action_from_with(acts, &mut rand::thread_rng())
+    }
+    pub fn rnd_action_from_with<R: Rng + ?Sized>(acts: &Vec<Self>, rng: &mut R) -> Option<Self> {
         match acts.is_empty() {
             true => None,
             _ => {
-                let index = rand::thread_rng().gen_range(0..=acts.len() - 1);
+                let index = rng.gen_range(0..=acts.len() - 1);
                 Some(*acts.get(index).expect("Error: empty possibale actions"))
             }
         }
@@ -45,7 +48,35 @@ pub struct Pot {
     pub value: Decimal,
     pub members: Vec<Position>,
     pub prev_street_end_size: Decimal,
+    // Сколько каждая позиция внесла на прошлых улицах. Вместе с positions_and_money текущей улицы
+    // это полный вклад в банк. Разница prev_street_end_size и суммы - мертвые деньги (например синтетический ривер).
+    #[serde(default)]
+    pub prev_streets_commits: HashMap<Position, Decimal>,
+    // Основной банк первым, дальше сайд-поты по росту алинов. Пересчитывается в do_action_on_position.
+    #[serde(default)]
+    pub side_pots: Vec<SidePot>,
+}
+impl Pot {
+    pub fn new(value: Decimal, members: Vec<Position>) -> Self {
+        Self {
+            value,
+            members,
+            prev_street_end_size: dec!(0),
+            prev_streets_commits: HashMap::new(),
+            side_pots: vec![],
+        }
+    }
+    pub fn dead_money(&self) -> Decimal {
+        self.prev_street_end_size - self.prev_streets_commits.values().sum::<Decimal>()
+    }
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SidePot {
+    pub value: Decimal,
+    // Позиции, которые могут выиграть этот банк: не в фолде и с алином не меньше уровня банка.
+    pub eligible: Vec<Position>,
 }
+#[derive(Debug, Clone)]
 pub struct PreflopGame {
     pub players: Vec<Player>,
     pub positions_and_money: HashMap<Position, Decimal>,
@@ -53,6 +84,7 @@ pub struct PreflopGame {
     pub main_pot: Pot,
     pub min_bet: Decimal,
     pub dead_cards: Vec<Card>,
+    pub table: TableConfig,
 }
 impl Game for PreflopGame {
     fn cards(&self) -> Option<&Vec<Card>> {
@@ -91,79 +123,89 @@ impl Game for PreflopGame {
     fn is_preflop_game(&self) -> bool {
         true
     }
+    fn table(&self) -> TableConfig {
+        self.table
+    }
 }
 impl PreflopGame {
     pub fn new_with_lock_cards(lock_cards: &Vec<Card>) -> Self {
+        Self::new_with_lock_cards_and_rng(lock_cards, &mut rand::thread_rng())
+    }
+    pub fn new_with_rng<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::new_with_lock_cards_and_rng(&vec![], rng)
+    }
+    pub fn new_with_lock_cards_and_rng<R: Rng + ?Sized>(
+        lock_cards: &Vec<Card>,
+        rng: &mut R,
+    ) -> Self {
+        Self::new_with_table(TableConfig::default(), lock_cards, rng)
+    }
+    pub fn new_with_table<R: Rng + ?Sized>(
+        table: TableConfig,
+        lock_cards: &Vec<Card>,
+        rng: &mut R,
+    ) -> Self {
+        // Раздаем в порядке префлопа, как и раньше для 6-max. Стеки случайные в бб.
         let mut dead_cards = lock_cards.clone();
-        let player_utg = Self::make_player_modify_dedcards_after_it(Position::Utg, &mut dead_cards);
-        let player_mp = Self::make_player_modify_dedcards_after_it(Position::Mp, &mut dead_cards);
-        let player_co = Self::make_player_modify_dedcards_after_it(Position::Co, &mut dead_cards);
-        let player_btn = Self::make_player_modify_dedcards_after_it(Position::Btn, &mut dead_cards);
-        let player_sb = Self::make_player_modify_dedcards_after_it(Position::Sb, &mut dead_cards);
-        let player_bb = Self::make_player_modify_dedcards_after_it(Position::Bb, &mut dead_cards);
-
-        Self {
-            players: vec![
-                player_utg, player_mp, player_co, player_btn, player_sb, player_bb,
-            ],
-            positions_and_money: HashMap::from([
-                (Position::Utg, dec!(0)),
-                (Position::Mp, dec!(0)),
-                (Position::Co, dec!(0)),
-                (Position::Btn, dec!(0)),
-                (Position::Sb, dec!(0.5)),
-                (Position::Bb, dec!(1)),
-            ]),
-            folded_positions: HashSet::new(),
-            main_pot: Pot {
-                value: dec!(1.5),
-                members: vec![Position::Sb, Position::Bb],
-                prev_street_end_size: dec!(0),
-            },
-            min_bet: dec!(1),
-            dead_cards,
+        let players = table
+            .preflop_order()
+            .into_iter()
+            .map(|position| {
+                let hole_cards = table.variant().hole_cards();
+                let mut player = Player::rnd_player_with(position, hole_cards, &dead_cards, rng);
+                player.stack_size *= table.blinds().bb;
+                dead_cards.extend_from_slice(&player.hand.cards);
+                player
+            })
+            .collect::<Vec<Player>>();
+        Self::from_players(table, players, dead_cards)
+    }
+    pub fn from_players(
+        table: TableConfig,
+        mut players: Vec<Player>,
+        dead_cards: Vec<Card>,
+    ) -> Self {
+        /* dead_cards уже должны содержать карты игроков, а players - по одному на каждую позицию стола.
+        - Анте сразу вычитаются из стека и лежат в банке как вклад прошлых улиц (мертвые деньги, не ставка).
+        - Блайнды и страддл это ставки улицы. Если стека не хватает, то игрок ставит все что есть.
+         */
+        let mut positions_and_money = HashMap::new();
+        let mut antes = HashMap::new();
+        for player in players.iter_mut() {
+            let pos = player.position;
+            let ante = table.posted_ante(pos).min(player.stack_size);
+            if ante > Decimal::ZERO {
+                player.stack_size -= ante;
+                antes.insert(pos, ante);
+            }
+            positions_and_money.insert(pos, table.posted_blind(pos).min(player.stack_size));
         }
-    }
-    pub fn new() -> Self {
-        let mut dead_cards = vec![];
-        let player_utg = Self::make_player_modify_dedcards_after_it(Position::Utg, &mut dead_cards);
-        let player_mp = Self::make_player_modify_dedcards_after_it(Position::Mp, &mut dead_cards);
-        let player_co = Self::make_player_modify_dedcards_after_it(Position::Co, &mut dead_cards);
-        let player_btn = Self::make_player_modify_dedcards_after_it(Position::Btn, &mut dead_cards);
-        let player_sb = Self::make_player_modify_dedcards_after_it(Position::Sb, &mut dead_cards);
-        let player_bb = Self::make_player_modify_dedcards_after_it(Position::Bb, &mut dead_cards);
-
-        Self {
-            players: vec![
-                player_utg, player_mp, player_co, player_btn, player_sb, player_bb,
-            ],
-            positions_and_money: HashMap::from([
-                (Position::Utg, dec!(0)),
-                (Position::Mp, dec!(0)),
-                (Position::Co, dec!(0)),
-                (Position::Btn, dec!(0)),
-                (Position::Sb, dec!(0.5)),
-                (Position::Bb, dec!(1)),
-            ]),
+        let members = table
+            .preflop_order()
+            .into_iter()
+            .filter(|pos| positions_and_money.get(pos).is_some_and(|&m| m > dec!(0)))
+            .collect::<Vec<Position>>();
+        let antes_sum = antes.values().sum::<Decimal>();
+        let mut main_pot = Pot::new(
+            antes_sum + positions_and_money.values().sum::<Decimal>(),
+            members,
+        );
+        main_pot.prev_street_end_size = antes_sum;
+        main_pot.prev_streets_commits = antes;
+        let mut game = Self {
+            players,
+            positions_and_money,
             folded_positions: HashSet::new(),
-            main_pot: Pot {
-                value: dec!(1.5),
-                members: vec![Position::Sb, Position::Bb],
-                prev_street_end_size: dec!(0),
-            },
-            min_bet: dec!(1),
+            main_pot,
+            min_bet: table.preflop_min_bet(),
             dead_cards,
-        }
+            table,
+        };
+        game.recalculate_side_pots();
+        game
     }
-    fn make_player_modify_dedcards_after_it(
-        position: Position,
-        dead_cards: &mut Vec<Card>,
-    ) -> Player {
-        let player = Player::rnd_player(position, &dead_cards);
-        for &card in player.hand.cards.iter() {
-            dead_cards.push(card);
-        }
-        player
+    pub fn new() -> Self {
+        Self::new_with_lock_cards(&vec![])
     }
     pub fn player_by_position_as_ref(&self, position: Position) -> &Player {
         self.players
@@ -0,0 +1,270 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+
+use crate::eval_hand::real_strength;
+use crate::hand::SuitMap;
+use crate::range::ALL_HANDS;
+use crate::{seeded_rng, Card, GameRng, Hand};
+
+pub const PREFLOP_RANKING_FILE: &str = "preflop_ranking.txt";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflopRank {
+    pub vs_random: f64,   // Олл-ин эквити против случайной руки.
+    pub vs_top: Vec<f64>, // Против топ X% рук, X из PreflopRanking::top_percents.
+}
+
+/*
+Таблица силы всех 16432 стартовых рук PLO с точностью до мастей (hand::SuitMap::canonical).
+- generate: Монте-Карло, samples раздач на руку. Сначала эквити против случайной руки, по ней
+  руки сортируются, и топ X% это лучшие руки по этому порядку (с учетом числа комбо каждой руки).
+  Потом эквити против топ X% для каждого X. Если в топ X% нет ни одной руки без карт нашей руки
+  (очень маленький X), то ошибка.
+- Файл: первая строка "top|10|20|50", дальше "AsAhKsKh|0.6823|0.6012|...". Руки канонические.
+*/
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PreflopRanking {
+    pub top_percents: Vec<u8>,
+    pub hands: BTreeMap<Hand, PreflopRank>,
+}
+impl PreflopRanking {
+    pub fn canonical_hand(hand: &Hand) -> Hand {
+        SuitMap::canonical(&[], &[hand]).hand(hand)
+    }
+    // Канонические руки и сколько реальных рук в каждой.
+    pub fn canonical_hands() -> BTreeMap<Hand, usize> {
+        let mut hands = BTreeMap::new();
+        for hand in ALL_HANDS.iter() {
+            *hands.entry(Self::canonical_hand(hand)).or_insert(0) += 1;
+        }
+        hands
+    }
+    pub fn generate(samples: u64, top_percents: &[u8], seed: u64) -> Result<Self, String> {
+        Self::generate_for(&Self::canonical_hands(), samples, top_percents, seed)
+    }
+    fn generate_for(
+        hands: &BTreeMap<Hand, usize>,
+        samples: u64,
+        top_percents: &[u8],
+        seed: u64,
+    ) -> Result<Self, String> {
+        let mut rng = seeded_rng(seed);
+        let vs_random = hands
+            .keys()
+            .map(|hand| Ok((hand.clone(), equity_vs_pool(hand, None, samples, &mut rng)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut by_strength = vs_random.clone();
+        by_strength.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let total_combos = hands.values().sum::<usize>();
+
+        let mut vs_top = vec![vec![]; vs_random.len()];
+        for &percent in top_percents {
+            // Реальные руки топ X%: берем канонические руки по силе, пока хватает комбо.
+            let limit = total_combos * percent as usize / 100;
+            let mut combos = 0;
+            let mut top = HashSet::new();
+            for (hand, _) in by_strength.iter() {
+                let count = hands[hand];
+                if combos + count > limit && combos > 0 {
+                    break;
+                }
+                combos += count;
+                top.insert(hand.clone());
+            }
+            let pool = ALL_HANDS
+                .iter()
+                .filter(|hand| top.contains(&Self::canonical_hand(hand)))
+                .collect::<Vec<&Hand>>();
+            for (i, (hand, _)) in vs_random.iter().enumerate() {
+                vs_top[i].push(equity_vs_pool(hand, Some(&pool), samples, &mut rng)?);
+            }
+        }
+        let hands = vs_random
+            .into_iter()
+            .zip(vs_top)
+            .map(|((hand, vs_random), vs_top)| (hand, PreflopRank { vs_random, vs_top }))
+            .collect();
+        Ok(Self {
+            top_percents: top_percents.to_vec(),
+            hands,
+        })
+    }
+    pub fn get(&self, hand: &Hand) -> Option<&PreflopRank> {
+        self.hands.get(&Self::canonical_hand(hand))
+    }
+    // Место руки по эквити против случайной руки, 1 - лучшая.
+    pub fn place(&self, hand: &Hand) -> Option<usize> {
+        let rank = self.get(hand)?;
+        Some(
+            self.hands
+                .values()
+                .filter(|other| other.vs_random > rank.vs_random)
+                .count()
+                + 1,
+        )
+    }
+    pub fn save(&self, file_name: &str) -> io::Result<()> {
+        let mut f = io::BufWriter::new(File::create(file_name)?);
+        let header = self
+            .top_percents
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>();
+        writeln!(f, "top|{}", header.join("|"))?;
+        for (hand, rank) in self.hands.iter() {
+            let cards = hand.cards.iter().map(|c| c.to_string()).collect::<String>();
+            let values = std::iter::once(rank.vs_random)
+                .chain(rank.vs_top.iter().copied())
+                .map(|v| format!("{:.4}", v))
+                .collect::<Vec<_>>();
+            writeln!(f, "{}|{}", cards, values.join("|"))?;
+        }
+        f.flush()
+    }
+    pub fn load(file_name: &str) -> io::Result<Self> {
+        let bad_line = |ln: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Error: bad preflop ranking line '{}'", ln),
+            )
+        };
+        let reader = io::BufReader::new(File::open(file_name)?);
+        let mut lines = reader.lines();
+        let header = lines.next().ok_or_else(|| bad_line(""))??;
+        let top_percents = header
+            .strip_prefix("top")
+            .ok_or_else(|| bad_line(&header))?
+            .split('|')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u8>().map_err(|_| bad_line(&header)))
+            .collect::<io::Result<Vec<u8>>>()?;
+
+        let mut hands = BTreeMap::new();
+        for line in lines {
+            let ln = line?;
+            let mut iter = ln.split('|');
+            let cards_str = iter.next().ok_or_else(|| bad_line(&ln))?;
+            let hand = cards_str.parse::<Hand>().map_err(|_| bad_line(&ln))?;
+            let values = iter
+                .map(|s| s.parse::<f64>().map_err(|_| bad_line(&ln)))
+                .collect::<io::Result<Vec<f64>>>()?;
+            if values.len() != top_percents.len() + 1 {
+                return Err(bad_line(&ln));
+            }
+            let rank = PreflopRank {
+                vs_random: values[0],
+                vs_top: values[1..].to_vec(),
+            };
+            hands.insert(hand, rank);
+        }
+        Ok(Self {
+            top_percents,
+            hands,
+        })
+    }
+}
+
+// Эквити руки хедз-ап олл-ин префлоп. pool None - соперник случайная рука.
+fn equity_vs_pool(
+    hand: &Hand,
+    pool: Option<&[&Hand]>,
+    samples: u64,
+    rng: &mut GameRng,
+) -> Result<f64, String> {
+    let mut deck = Card::deck();
+    deck.retain(|card| !hand.cards.contains(card));
+    // Руки соперника без наших карт, выбираются один раз.
+    let pool = pool.map(|pool| {
+        pool.iter()
+            .filter(|villain| villain.cards.iter().all(|c| !hand.cards.contains(c)))
+            .collect::<Vec<_>>()
+    });
+    if pool.as_ref().is_some_and(|pool| pool.is_empty()) {
+        return Err(format!("Error: no hands in pool without cards of {}", hand));
+    }
+    let mut share = 0.0;
+    for _ in 0..samples {
+        let villain = match &pool {
+            Some(pool) => (*pool[rng.gen_range(0..pool.len())]).clone(),
+            None => {
+                let (cards, _) = deck.partial_shuffle(rng, 4);
+                Hand::from_cards(cards.to_vec()).unwrap_or_else(|_| unreachable!())
+            }
+        };
+        let mut rest = deck
+            .iter()
+            .copied()
+            .filter(|card| !villain.cards.contains(card))
+            .collect::<Vec<Card>>();
+        let (board, _) = rest.partial_shuffle(rng, 5);
+        share += match real_strength(hand, board).cmp(&real_strength(&villain, board)) {
+            std::cmp::Ordering::Greater => 1.0,
+            std::cmp::Ordering::Equal => 0.5,
+            std::cmp::Ordering::Less => 0.0,
+        };
+    }
+    Ok(share / samples as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::hand;
+
+    #[test]
+    fn all_canonical_starting_hands() {
+        let hands = PreflopRanking::canonical_hands();
+        assert_eq!(hands.len(), 16432);
+        assert_eq!(hands.values().sum::<usize>(), 270_725);
+        // AAKK ds: 6 вариантов мастей с одинаковыми мастями у пар.
+        let aakk = PreflopRanking::canonical_hand(&hand("As Ks Ah Kh"));
+        assert_eq!(hands[&aakk], 6);
+        assert_eq!(aakk, PreflopRanking::canonical_hand(&hand("Ad Kd Ac Kc")));
+    }
+    #[test]
+    fn generate_save_and_load() {
+        let strong = PreflopRanking::canonical_hand(&hand("As Ks Ah Kh"));
+        let weak = PreflopRanking::canonical_hand(&hand("7s 5h 3d 2c"));
+        let middle = PreflopRanking::canonical_hand(&hand("Js Ts 9h 8h"));
+        let hands = [(strong.clone(), 6), (weak.clone(), 24), (middle.clone(), 6)]
+            .into_iter()
+            .collect::<BTreeMap<Hand, usize>>();
+        let ranking = PreflopRanking::generate_for(&hands, 1_000, &[20, 50], 4).unwrap();
+        assert_eq!(ranking.hands.len(), 3);
+        assert_eq!(ranking.place(&hand("Ad Kd Ac Kc")), Some(1));
+        assert_eq!(ranking.place(&weak), Some(3));
+        let strong_rank = ranking.get(&strong).unwrap();
+        assert!(strong_rank.vs_random > 0.6);
+        assert_eq!(strong_rank.vs_top.len(), 2);
+        // Против лучших рук эквити меньше, чем против случайной.
+        let weak_rank = ranking.get(&weak).unwrap();
+        assert!(weak_rank.vs_top[0] < weak_rank.vs_random);
+
+        let file_name = std::env::temp_dir().join("neiro_om_preflop_ranking_test.txt");
+        let file_name = file_name.to_str().unwrap();
+        ranking.save(file_name).unwrap();
+        let loaded = PreflopRanking::load(file_name).unwrap();
+        std::fs::remove_file(file_name).unwrap();
+        assert_eq!(loaded.top_percents, vec![20, 50]);
+        assert_eq!(loaded.hands.len(), 3);
+        let loaded_rank = loaded.get(&strong).unwrap();
+        assert!((loaded_rank.vs_random - strong_rank.vs_random).abs() < 1e-4);
+        assert_eq!(loaded.place(&weak), Some(3));
+    }
+    #[test]
+    fn pool_without_free_hands_is_error() {
+        // Все руки пула с нашими картами: раньше выбор соперника зависал.
+        let aakk = hand("As Ks Ah Kh");
+        let blocked = [hand("As Qd Jc 2c"), hand("Kh Qd Jc 2c")];
+        let pool = blocked.iter().collect::<Vec<&Hand>>();
+        let mut rng = seeded_rng(1);
+        assert!(equity_vs_pool(&aakk, Some(&pool), 100, &mut rng).is_err());
+        let free = hand("Ad Kd Ac Kc");
+        let pool = vec![&blocked[0], &free];
+        let equity = equity_vs_pool(&aakk, Some(&pool), 100, &mut rng).unwrap();
+        assert!(equity > 0.3 && equity < 0.7);
+    }
+}
@@ -0,0 +1,247 @@
+use lazy_static::lazy_static;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Card, FakeHand, Hand, Rank};
+
+/*
+Взвешенный диапазон рук PLO (4 карты): рука -> вес от 0 до 1.
+Синтаксис Range::parse - термы через запятую, у каждого терма необязательный вес после ':':
+- "AA**" - 4 ранга, '*' это любой ранг. Ранги из шаблона должны быть в руке, остальные любые.
+- "KQJT ds" - после рангов масти: ds - две масти, ss - ровно одна одномастная пара, os - все разные.
+- "$ds", "$ss", "$os" - любые руки с такими мастями, то же что "**** ds".
+- "AA** ds:0.5, KK**:0.25" - если рука подходит под несколько термов, вес берется у последнего.
+Вес 0 убирает руку из диапазона.
+*/
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Range {
+    pub combos: BTreeMap<Hand, f64>,
+}
+impl Range {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let terms = s
+            .split(',')
+            .map(|term| term.trim())
+            .filter(|term| !term.is_empty())
+            .map(RangeTerm::parse)
+            .collect::<Result<Vec<_>, String>>()?;
+        if terms.is_empty() {
+            return Err(String::from("Error: empty range"));
+        }
+        let mut range = Self::default();
+        for hand in ALL_HANDS.iter() {
+            if let Some(term) = terms.iter().rev().find(|term| term.matches(hand)) {
+                range.add(hand.clone(), term.weight);
+            }
+        }
+        Ok(range)
+    }
+    // Диапазон из корзин FakeHand: каждая реальная рука получает вес своей корзины.
+    pub fn from_fake_hands(buckets: &[(FakeHand, f64)]) -> Self {
+        let weights = buckets.iter().cloned().collect::<HashMap<FakeHand, f64>>();
+        let mut range = Self::default();
+        for hand in ALL_HANDS.iter() {
+            if let Some(&weight) = weights.get(&FakeHand::from(hand)) {
+                range.add(hand.clone(), weight);
+            }
+        }
+        range
+    }
+    pub fn add(&mut self, hand: Hand, weight: f64) {
+        if weight > 0.0 {
+            self.combos.insert(hand, weight.min(1.0));
+        } else {
+            self.combos.remove(&hand);
+        }
+    }
+    // Убирает руки, в которых есть карты борда или другие известные карты.
+    pub fn without_blocked(&self, cards: &[Card]) -> Self {
+        let combos = self
+            .combos
+            .iter()
+            .filter(|(hand, _)| hand.cards.iter().all(|card| !cards.contains(card)))
+            .map(|(hand, &weight)| (hand.clone(), weight))
+            .collect();
+        Self { combos }
+    }
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+    pub fn total_weight(&self) -> f64 {
+        self.combos.values().sum()
+    }
+    pub fn weight(&self, hand: &Hand) -> f64 {
+        self.combos.get(hand).copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SuitFilter {
+    Any,
+    Ds,
+    Ss,
+    Os,
+}
+
+struct RangeTerm {
+    ranks: Vec<Rank>, // Без '*'.
+    suits: SuitFilter,
+    weight: f64,
+}
+impl RangeTerm {
+    fn parse(term: &str) -> Result<Self, String> {
+        let (body, weight) = match term.split_once(':') {
+            Some((body, weight)) => {
+                let weight = weight
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("Error: bad weight in range term '{}'", term))?;
+                if !(0.0..=1.0).contains(&weight) {
+                    return Err(format!("Error: weight must be from 0 to 1 in '{}'", term));
+                }
+                (body.trim(), weight)
+            }
+            None => (term, 1.0),
+        };
+        let (ranks_part, suits_part) = match body.strip_prefix('$') {
+            Some(suits) => ("****", Some(suits)),
+            None => {
+                let mut parts = body.split_ascii_whitespace();
+                let ranks = parts.next().unwrap_or_default();
+                let suits = parts.next();
+                if parts.next().is_some() {
+                    return Err(format!("Error: too many parts in range term '{}'", term));
+                }
+                (ranks, suits)
+            }
+        };
+        if ranks_part.chars().count() != 4 {
+            return Err(format!("Error: range term '{}' must have 4 ranks", term));
+        }
+        let mut ranks = vec![];
+        for c in ranks_part.chars().filter(|&c| c != '*') {
+            let rank = Rank::from_str(&c.to_ascii_uppercase().to_string())
+                .ok_or_else(|| format!("Error: bad rank '{}' in range term '{}'", c, term))?;
+            ranks.push(rank);
+        }
+        let suits = match suits_part.map(|s| s.to_ascii_lowercase()).as_deref() {
+            None => SuitFilter::Any,
+            Some("ds") => SuitFilter::Ds,
+            Some("ss") => SuitFilter::Ss,
+            Some("os") => SuitFilter::Os,
+            Some(other) => {
+                return Err(format!(
+                    "Error: bad suits '{}' in range term '{}'",
+                    other, term
+                ))
+            }
+        };
+        Ok(Self {
+            ranks,
+            suits,
+            weight,
+        })
+    }
+    fn matches(&self, hand: &Hand) -> bool {
+        let suits_ok = match self.suits {
+            SuitFilter::Any => true,
+            SuitFilter::Ds => hand.is_double_suited(),
+            SuitFilter::Os => hand.is_off_suited(),
+            SuitFilter::Ss => !hand.is_double_suited() && !hand.is_off_suited(),
+        };
+        if !suits_ok {
+            return false;
+        }
+        let mut left = hand.cards.iter().map(|card| card.rank).collect::<Vec<_>>();
+        self.ranks
+            .iter()
+            .all(|rank| match left.iter().position(|r| r == rank) {
+                Some(i) => {
+                    left.swap_remove(i);
+                    true
+                }
+                None => false,
+            })
+    }
+}
+
+lazy_static! {
+    // Все 270725 рук из 4 карт.
+    pub(crate) static ref ALL_HANDS: Vec<Hand> = {
+        let deck = Card::deck();
+        let n = deck.len();
+        let mut hands = Vec::with_capacity(270_725);
+        for i in 0..n {
+            for j in i + 1..n {
+                for k in j + 1..n {
+                    for l in k + 1..n {
+                        hands.push(
+                            Hand::new(deck[i], deck[j], deck[k], deck[l])
+                                .unwrap_or_else(|_| unreachable!()),
+                        );
+                    }
+                }
+            }
+        }
+        hands
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand::{FakeCard, FakeRank, FakeSuitKind, Pairing};
+    use crate::test_utils::{cards, hand};
+
+    #[test]
+    fn parse_range_syntax() {
+        // AA**: C(4,2) пар тузов * C(48,2) остальных + руки с тремя и четырьмя тузами.
+        let aces = Range::parse("AA**").unwrap();
+        assert_eq!(aces.len(), 6 * 1128 + 4 * 48 + 1);
+
+        // KQJT ds: 2 масти на пары карт, 4 * 3 выбора мастей * 3 разбиения на пары.
+        let broadway = Range::parse("kqjt DS").unwrap();
+        assert_eq!(broadway.len(), 36);
+        assert!(broadway.combos.keys().all(|hand| hand.is_double_suited()));
+
+        let weighted = Range::parse("AA** ds:0.5, AAKK ds").unwrap();
+        let aakk = hand("Ah Kh As Ks");
+        let aaqq = hand("Ah Qh As Qs");
+        assert_eq!(weighted.weight(&aakk), 1.0);
+        assert_eq!(weighted.weight(&aaqq), 0.5);
+
+        let removed = Range::parse("AA**, AAKK:0").unwrap();
+        assert_eq!(removed.len(), aces.len() - 36);
+
+        let ds = Range::parse("$ds").unwrap();
+        assert!(ds.combos.keys().all(|hand| hand.is_double_suited()));
+        for bad in ["", "AA*", "AA** xx", "AAX*", "AA**:2", "AA** ds ss"] {
+            assert!(Range::parse(bad).is_err(), "{}", bad);
+        }
+    }
+    #[test]
+    fn board_blocks_and_fake_hand_buckets() {
+        let aces = Range::parse("AA**").unwrap();
+        let blocked = aces.without_blocked(&cards("Ad 7c 2h"));
+        // Осталось 3 туза: C(3,2) пары * C(46,2) без 7c и 2h + C(3,3)=1 * 46 с тремя тузами + 0 каре.
+        assert_eq!(blocked.len(), 3 * 1035 + 46);
+        assert_eq!(blocked.weight(&hand("Ad Ah 3c 4c")), 0.0);
+
+        let ace = || FakeCard {
+            rank: FakeRank::Ace,
+        };
+        let king = || FakeCard {
+            rank: FakeRank::King,
+        };
+        let bucket = FakeHand {
+            cards: vec![ace(), ace(), king(), king()],
+            kind: FakeSuitKind::Ds,
+            paired: Pairing::DoublePaired,
+        };
+        let range = Range::from_fake_hands(&[(bucket, 0.3)]);
+        assert_eq!(range.len(), Range::parse("AAKK ds").unwrap().len());
+        assert!((range.total_weight() - 0.3 * range.len() as f64).abs() < 1e-9);
+    }
+}
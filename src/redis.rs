@@ -7,7 +7,8 @@ use std::{
     io::Write,
 };
 
-use crate::{ActionKind, FakePostflopPause, FakePreflopPause, Position};
+use crate::cfr::{frequencies_from_winrates, sample};
+use crate::{ActionKind, FakePostflopPause, FakePreflopPause, GameRng, Position};
 
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord)]
 pub enum RedisStreet {
@@ -65,6 +66,9 @@ impl RedisUtils {
             crate::Position::Mp => key.push('3'),
             crate::Position::Co => key.push('4'),
             crate::Position::Btn => key.push('5'),
+            crate::Position::Utg1 => key.push('6'),
+            crate::Position::Utg2 => key.push('7'),
+            crate::Position::Lj => key.push('8'),
         }
         key.push('|');
         key.push_str(format!("{:?}", fake.my_fake_hand).trim());
@@ -88,14 +92,16 @@ impl RedisUtils {
         3-raise50
         4-raise75
         5-raise100
+        6-raise-allin (только безлимит, больше пота)
         Для рейза определение id такое:
         - Если рейз в возможных действиях один, то это всегда 50%
         - Если рейза в возможных действиях 2 и это максимальный сайзинг, то это всегда 75% иначе 50%
         - Если рейза в возможных действиях три и это максимальный сайзинг, то это всегда 100%, если минимальный, то 50%, иначе 75%
+        - Если рейза четыре (безлимит), то максимальный это алын, дальше по убыванию 100%, 75%, 50%
          */
         let mut count_raises = 0;
         let mut max_raise = Decimal::MIN;
-        let mut min_raise = Decimal::MIN;
+        let mut min_raise = Decimal::MAX;
         possible_act
             .iter()
             .filter(|&&x| {
@@ -140,6 +146,20 @@ impl RedisUtils {
                         4
                     }
                 }
+                4 => {
+                    let mut raises = possible_act
+                        .iter()
+                        .filter_map(|&x| match x {
+                            ActionKind::Raise(val) => Some(val),
+                            _ => None,
+                        })
+                        .collect::<Vec<Decimal>>();
+                    raises.sort_unstable();
+                    3 + raises
+                        .iter()
+                        .position(|&x| x == val)
+                        .unwrap_or_else(|| unreachable!()) as u8
+                }
                 _ => unreachable!(),
             },
         }
@@ -172,15 +192,18 @@ impl RedisUtils {
 
         redis::cmd("FLUSHALL").query(&mut con)?;
         */
+        let freqs = frequencies_by_key(record);
         for ((key, id), (result, hands)) in record {
             let winrate = ((result / hands) * dec!(100)).round();
+            let freq = freqs[&(key.clone(), *id)];
             writeln!(
                 f,
-                "xadd {} {}-9 hands {} winrate {}",
+                "xadd {} {}-9 hands {} winrate {} freq {}",
                 key,
                 id,
                 hands.to_i32().unwrap(),
-                winrate.to_i32().unwrap()
+                winrate.to_i32().unwrap(),
+                freq
             )
             .unwrap();
             // con.xadd("0#preflop#1|2|3|4", "0-1", &[("hands", 1000), ("winr", 15)])?;
@@ -190,6 +213,7 @@ impl RedisUtils {
                 &[
                     ("hands", hands.to_i32().unwrap()),
                     ("winrate", winrate.to_i32().unwrap()),
+                    ("freq", freq),
                 ],
             )?;
         }
@@ -241,35 +265,18 @@ impl RedisUtils {
          */
         let key_debug = key.clone();
         // print!("{}", key);
-        let range: StreamRangeReply = con.xrange_all(key)?;
-
         let mut bests_id = vec![];
 
         // let mut raise_result = 0;
         // let mut raise_hands = 0;
 
-        for stream_id in range.ids {
-            // print!("\n{} ", stream_id.id);
-            let significant_part_of_id = &stream_id.id[0..=0];
-            let id = significant_part_of_id.parse::<u8>().unwrap();
-
-            let mut winrate = None;
-            let mut hands = None;
-            for (key, value) in stream_id.map {
-                let val = from_redis_value::<isize>(&value)?;
-                // print!(" {}: {:?}", key, val);
-                if key == "winrate" {
-                    winrate = Some(val);
-                } else {
-                    hands = Some(val);
-                }
-            }
+        for record in read_actions(key, con)? {
             // if id == 3 || id == 4 || id == 5 {
             //     raise_result += hands.unwrap() * winrate.unwrap() / 100;
             //     raise_hands += hands.unwrap();
             // } else {
-            if hands.is_some() && hands.unwrap() >= 5 {
-                bests_id.push((winrate.unwrap(), id));
+            if record.hands >= 5 {
+                bests_id.push((record.winrate, record.id));
             }
             // }
         }
@@ -294,83 +301,203 @@ impl RedisUtils {
             Ok(None)
         }
     }
+    /*
+    Смешанная стратегия из записи в редиске вместо одного лучшего действия (best_action).
+    - Как в best_action, действия меньше чем с 5 руками не считаются.
+    - Частоты берутся из поля freq (проценты). В записях старых поколений его нет, тогда частоты
+      считаются по винрейтам (cfr::frequencies_from_winrates).
+    - Действия, которых нет в possible_act, выкидываются, остальные частоты нормируются.
+      Пустой результат - то же, что Ok(None) в best_action.
+    */
+    pub fn action_distribution(
+        possible_act: &Vec<ActionKind>,
+        key: String,
+        con: &mut Connection,
+    ) -> RedisResult<Vec<(ActionKind, f64)>> {
+        Ok(distribution_from_records(
+            &read_actions(key, con)?,
+            possible_act,
+        ))
+    }
+    pub fn sample_action(
+        possible_act: &Vec<ActionKind>,
+        key: String,
+        con: &mut Connection,
+        rng: &mut GameRng,
+    ) -> RedisResult<Option<ActionKind>> {
+        let distribution = Self::action_distribution(possible_act, key, con)?;
+        Ok(sample(&distribution, rng))
+    }
+}
+
+// Действие из потока редиски: "id-9 hands N winrate W freq F".
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RedisAction {
+    id: u8,
+    hands: isize,
+    winrate: isize,
+    freq: Option<isize>,
+}
+fn read_actions(key: String, con: &mut Connection) -> RedisResult<Vec<RedisAction>> {
+    let range: StreamRangeReply = con.xrange_all(key)?;
+    let mut result = Vec::with_capacity(range.ids.len());
+    for stream_id in range.ids {
+        // print!("\n{} ", stream_id.id);
+        let significant_part_of_id = &stream_id.id[0..=0];
+        let id = significant_part_of_id.parse::<u8>().unwrap();
+
+        let mut action = RedisAction {
+            id,
+            hands: 0,
+            winrate: 0,
+            freq: None,
+        };
+        for (key, value) in stream_id.map {
+            let val = from_redis_value::<isize>(&value)?;
+            // print!(" {}: {:?}", key, val);
+            match key.as_str() {
+                "winrate" => action.winrate = val,
+                "freq" => action.freq = Some(val),
+                _ => action.hands = val,
+            }
+        }
+        result.push(action);
+    }
+    Ok(result)
+}
+fn distribution_from_records(
+    records: &[RedisAction],
+    possible_act: &Vec<ActionKind>,
+) -> Vec<(ActionKind, f64)> {
+    let records = records
+        .iter()
+        .filter(|record| record.hands >= 5)
+        .collect::<Vec<_>>();
+    let freqs = if records.iter().all(|record| record.freq.is_some()) {
+        records
+            .iter()
+            .map(|record| record.freq.unwrap_or_default() as f64)
+            .collect::<Vec<f64>>()
+    } else {
+        let winrates = records
+            .iter()
+            .map(|record| (record.winrate as f64, record.hands as f64))
+            .collect::<Vec<_>>();
+        frequencies_from_winrates(&winrates)
+    };
+    // Разные id могут дать одно действие (мало рейзов в possible_act), тогда частоты складываются.
+    let mut result: Vec<(ActionKind, f64)> = vec![];
+    for (record, freq) in records.iter().zip(freqs) {
+        let Some(act) = action_by_id(record.id, possible_act) else {
+            continue;
+        };
+        match result.iter_mut().find(|(a, _)| *a == act) {
+            Some((_, sum)) => *sum += freq,
+            None => result.push((act, freq)),
+        }
+    }
+    let sum = result.iter().map(|(_, freq)| freq).sum::<f64>();
+    if sum <= 0.0 {
+        return vec![];
+    }
+    result
+        .into_iter()
+        .map(|(act, freq)| (act, freq / sum))
+        .collect()
+}
+// Частоты (в процентах) действий каждого ключа по винрейтам, для поля freq в редиске.
+fn frequencies_by_key(
+    record: &BTreeMap<(String, u8), (Decimal, Decimal)>,
+) -> BTreeMap<(String, u8), i32> {
+    let mut by_key: BTreeMap<&String, Vec<(u8, f64, f64)>> = BTreeMap::new();
+    for ((key, id), (result, hands)) in record {
+        let winrate = (result / hands).to_f64().unwrap_or_default();
+        by_key
+            .entry(key)
+            .or_default()
+            .push((*id, winrate, hands.to_f64().unwrap_or_default()));
+    }
+    let mut result = BTreeMap::new();
+    for (key, actions) in by_key {
+        let winrates = actions
+            .iter()
+            .map(|&(_, winrate, hands)| (winrate, hands))
+            .collect::<Vec<_>>();
+        for ((id, _, _), freq) in actions.iter().zip(frequencies_from_winrates(&winrates)) {
+            result.insert((key.clone(), *id), (freq * 100.0).round() as i32);
+        }
+    }
+    result
 }
 fn pick_best_from_possible(
     bests_id: Vec<(isize, u8)>,
     possible_act: &Vec<ActionKind>,
 ) -> Option<ActionKind> {
-    let raises_in_possible_act = possible_act
+    for (_, action) in bests_id {
+        let founded = action_by_id(action, possible_act);
+        if founded.is_some() {
+            // println!("\npicked {:?}", founded);
+            return founded;
+        }
+    }
+    None
+}
+fn action_by_id(action: u8, possible_act: &Vec<ActionKind>) -> Option<ActionKind> {
+    // Рейзы по возрастанию, как в RedisUtils::get_action_id: 3 - минимальный, дальше по размеру,
+    // при четырех рейзах (безлимит) 6 - алын. Если рейзов меньше, старшие id - максимальный рейз.
+    let mut raises_in_possible_act = possible_act
         .iter()
         .filter_map(|&act| {
-            if let ActionKind::Raise(_) = act {
-                Some(act)
+            if let ActionKind::Raise(val) = act {
+                Some(val)
             } else {
                 None
             }
         })
         .collect::<Vec<_>>();
-    // Если нет рейзов+, если 1 рейз+, если 2 рейза+, если 3 рейза+
-    let first_raise = raises_in_possible_act.first().map(|x| *x);
-    let last_raise = raises_in_possible_act.last().map(|x| *x);
-    let mid_raise = if raises_in_possible_act.len() <= 2 {
-        first_raise.clone()
-    } else {
-        raises_in_possible_act.get(1).map(|x| *x)
+    raises_in_possible_act.sort_unstable();
+    let raise_by_id = |id: u8| {
+        let index = usize::min(
+            id as usize - 3,
+            raises_in_possible_act.len().checked_sub(1)?,
+        );
+        Some(ActionKind::Raise(raises_in_possible_act[index]))
     };
-    for (_, action) in bests_id {
-        let founded = match action {
-            0 => possible_act
-                .iter()
-                .find(|&&x| {
-                    if let ActionKind::Fold = x {
-                        true
-                    } else {
-                        false
-                    }
-                })
-                .map(|x| *x),
-            1 => possible_act
-                .iter()
-                .find(|&&x| {
-                    if let ActionKind::Check = x {
-                        true
-                    } else {
-                        false
-                    }
-                })
-                .map(|x| *x),
-            2 => possible_act
-                .iter()
-                .find(|&&x| {
-                    if let ActionKind::Call(_) = x {
-                        true
-                    } else {
-                        false
-                    }
-                })
-                .map(|x| *x),
-            3 => last_raise,
-            4 => mid_raise,
-            5 => first_raise,
-            // Рейзы всегда идут от большего к меньшему в possible_act.
-            // 3 | 4 | 5 => possible_act
-            //     .iter()
-            //     .find(|&&x| {
-            //         if let ActionKind::Raise(_) = x {
-            //             true
-            //         } else {
-            //             false
-            //         }
-            //     })
-            //     .map(|x| *x),
-            _ => panic!("error 1: cant find best action"),
-        };
-        if founded.is_some() {
-            // println!("\npicked {:?}", founded);
-            return founded;
-        }
+    match action {
+        0 => possible_act
+            .iter()
+            .find(|&&x| {
+                if let ActionKind::Fold = x {
+                    true
+                } else {
+                    false
+                }
+            })
+            .map(|x| *x),
+        1 => possible_act
+            .iter()
+            .find(|&&x| {
+                if let ActionKind::Check = x {
+                    true
+                } else {
+                    false
+                }
+            })
+            .map(|x| *x),
+        2 => possible_act
+            .iter()
+            .find(|&&x| {
+                if let ActionKind::Call(_) = x {
+                    true
+                } else {
+                    false
+                }
+            })
+            .map(|x| *x),
+        3..=6 => raise_by_id(action),
+        // Неизвестный id (другая версия записи) - запись пропускается.
+        _ => None,
     }
-    None
 }
 
 pub fn start_redis() -> redis::RedisResult<HashMap<String, usize>> {
@@ -407,3 +534,106 @@ pub fn start_redis() -> redis::RedisResult<HashMap<String, usize>> {
                                                                                 //con.xrange_all("0#preflop#1|2|3|4")?
     r
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: u8, hands: isize, winrate: isize, freq: Option<isize>) -> RedisAction {
+        RedisAction {
+            id,
+            hands,
+            winrate,
+            freq,
+        }
+    }
+    #[test]
+    fn action_id_round_trip() {
+        // Рейзы в possible_act от большего к меньшему, как в action::possible_action_kind.
+        let raises = [dec!(200), dec!(100), dec!(75), dec!(50)];
+        for count in 1..=raises.len() {
+            let mut possible_act = vec![ActionKind::Fold, ActionKind::Call(dec!(25))];
+            possible_act.extend(
+                raises[raises.len() - count..]
+                    .iter()
+                    .map(|&r| ActionKind::Raise(r)),
+            );
+            for &act in possible_act.iter() {
+                let id = RedisUtils::get_action_id(act, &possible_act);
+                assert_eq!(
+                    action_by_id(id, &possible_act),
+                    Some(act),
+                    "{} raises",
+                    count
+                );
+            }
+        }
+        // Безлимит: алын - 6, 100% - 5.
+        let possible_act = raises.map(ActionKind::Raise).to_vec();
+        assert_eq!(
+            RedisUtils::get_action_id(ActionKind::Raise(dec!(200)), &possible_act),
+            6
+        );
+        assert_eq!(
+            action_by_id(5, &possible_act),
+            Some(ActionKind::Raise(dec!(100)))
+        );
+        assert_eq!(action_by_id(2, &possible_act), None);
+    }
+    #[test]
+    fn frequencies_in_records() {
+        let possible_act = vec![
+            ActionKind::Fold,
+            ActionKind::Call(dec!(10)),
+            ActionKind::Raise(dec!(40)),
+        ];
+        // Частоты из записи, рейзы 3 и 5 при одном возможном рейзе - одно действие.
+        let records = [
+            record(0, 10, -5, Some(20)),
+            record(2, 10, 10, Some(40)),
+            record(3, 10, 20, Some(30)),
+            record(5, 10, 20, Some(10)),
+            record(1, 10, 50, Some(50)), // Чека нет в possible_act.
+            record(4, 3, 90, Some(50)),  // Мало рук.
+            record(6, 10, 20, Some(0)),  // Алын безлимита при одном рейзе - тот же рейз.
+            record(9, 10, 90, Some(50)), // Неизвестный id.
+        ];
+        let distribution = distribution_from_records(&records, &possible_act);
+        assert_eq!(
+            distribution,
+            vec![
+                (ActionKind::Fold, 0.2),
+                (ActionKind::Call(dec!(10)), 0.4),
+                (ActionKind::Raise(dec!(40)), 0.4),
+            ]
+        );
+
+        // Старое поколение без freq: по винрейтам, средний 10, лучше него только рейз.
+        let old = [
+            record(0, 10, 0, None),
+            record(2, 10, 10, None),
+            record(3, 10, 20, None),
+        ];
+        assert_eq!(
+            distribution_from_records(&old, &possible_act),
+            vec![
+                (ActionKind::Fold, 0.0),
+                (ActionKind::Call(dec!(10)), 0.0),
+                (ActionKind::Raise(dec!(40)), 1.0)
+            ]
+        );
+        assert!(distribution_from_records(&old, &vec![ActionKind::Check]).is_empty());
+
+        let mut full = BTreeMap::new();
+        full.insert((String::from("k"), 1), (dec!(50), dec!(100)));
+        full.insert((String::from("k"), 3), (dec!(20), dec!(100)));
+        full.insert((String::from("k"), 0), (dec!(-30), dec!(100)));
+        full.insert((String::from("other"), 2), (dec!(-1), dec!(10)));
+        let freqs = frequencies_by_key(&full);
+        // Средний винрейт 0.13: лучше него чек (0.5) и рейз (0.2).
+        assert_eq!(freqs[&(String::from("k"), 1)], 85);
+        assert_eq!(freqs[&(String::from("k"), 3)], 15);
+        assert_eq!(freqs[&(String::from("k"), 0)], 0);
+        assert_eq!(freqs[&(String::from("other"), 2)], 100);
+    }
+}
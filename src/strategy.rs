@@ -1,372 +1,515 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Display, Formatter};
+use std::iter;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use serde::{Deserialize, Serialize};
-use strum::IntoEnumIterator;
-use strum_macros::{EnumIter, EnumString};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::ActionKind;
+use crate::action::{already_commit_by_pos, max_current_commit_from_all};
+use crate::cfr::{frequencies_from_winrates, sample, Strategy};
+use crate::{ActionKind, Game, GameRng, GameVariant, Position};
 
 #[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct GraphPoint {
     pub node: Node,
     pub hands: usize,
     pub win: Decimal,
+    // Частота ноды среди нод той же точки решения. В старых файлах графов ее нет - 0.
+    #[serde(default)]
+    pub freq: Decimal,
 }
 impl GraphPoint {
     pub fn is_end_point(&self) -> bool {
-        match self.node {
-            Node::B50F
-            | Node::B50C
-            | Node::B50RF
-            | Node::B50RC
-            | Node::B50RRF
-            | Node::B50RRC
-            | Node::B75F
-            | Node::B75C
-            | Node::B75RF
-            | Node::B75RC
-            | Node::B75RRF
-            | Node::B75RRC
-            | Node::B100F
-            | Node::B100C
-            | Node::B100RF
-            | Node::B100RC
-            | Node::B100RRF
-            | Node::B100RRC
-            | Node::XB50F
-            | Node::XB50C
-            | Node::XB50RF
-            | Node::XB50RC
-            | Node::XB50RRF
-            | Node::XB50RRC
-            | Node::XB75F
-            | Node::XB75C
-            | Node::XB75RF
-            | Node::XB75RC
-            | Node::XB75RRF
-            | Node::XB75RRC
-            | Node::XB100F
-            | Node::XB100C
-            | Node::XB100RF
-            | Node::XB100RC
-            | Node::XB100RRF
-            | Node::XB100RRC
-            | Node::XX => true,
-            _ => false,
-        }
+        self.node.is_last_node()
     }
     pub fn new(node: Node) -> Self {
         GraphPoint {
             node,
             hands: 0,
             win: Decimal::ZERO,
+            freq: Decimal::ZERO,
         }
     }
     pub fn get_all_graph_points() -> Vec<Self> {
-        let mut result = Vec::with_capacity(57);
-        for node in Node::iter() {
-            result.push(GraphPoint::new(node));
+        Node::all().into_iter().map(GraphPoint::new).collect()
+    }
+    // Дерево ривера с руками и выигрышем каждой ноды, отступ - глубина ноды.
+    pub fn print_graph(points: &[GraphPoint]) {
+        let tree = river_tree();
+        let mut stack = vec![tree.root()];
+        while let Some(node) = stack.pop() {
+            let (h, w) = get_vals(node, points);
+            println!(
+                "{}{} [h: {}, w: {}]",
+                "\t".repeat(tree.depth(node)),
+                node,
+                h,
+                w
+            );
+            stack.extend(tree.children(node).iter().rev());
+        }
+    }
+    /*
+    Смешанная стратегия графа вместо одной лучшей ноды: в каждой точке решения частоты нод по их
+    винрейтам (cfr::frequencies_from_winrates). Считается перед записью графа поколения в файл.
+    */
+    pub fn set_frequencies(points: &mut [GraphPoint]) {
+        let prevs = iter::once(None).chain(
+            Node::all()
+                .into_iter()
+                .filter(|&n| n != Node::root())
+                .map(Some),
+        );
+        for prev in prevs {
+            for (node, freq) in Self::distribution_from_wins(points, prev) {
+                if let Some(point) = points.iter_mut().find(|p| p.node == node) {
+                    point.freq = Decimal::from_f64(freq).unwrap_or_default().round_dp(4);
+                }
+            }
         }
-        result
-    }
-    pub fn print_graph(points: &Vec<GraphPoint>) {
-        let (h1, w1) = get_vals(Node::B50, points);
-        let (h2, w2) = get_vals(Node::B50F, points);
-        let (h3, w3) = get_vals(Node::B50C, points);
-        let (h4, w4) = get_vals(Node::B50R, points);
-        let (h5, w5) = get_vals(Node::B50RF, points);
-        let (h6, w6) = get_vals(Node::B50RC, points);
-        let (h7, w7) = get_vals(Node::B50RA, points);
-        let (h8, w8) = get_vals(Node::B50RRF, points);
-        let (h9, w9) = get_vals(Node::B50RRC, points);
-        let (h10, w10) = get_vals(Node::B75F, points);
-        let (h11, w11) = get_vals(Node::B75, points);
-        let (h12, w12) = get_vals(Node::B75C, points);
-        let (h13, w13) = get_vals(Node::B75R, points);
-        let (h14, w14) = get_vals(Node::B75RF, points);
-        let (h15, w15) = get_vals(Node::B75RC, points);
-        let (h16, w16) = get_vals(Node::B75RA, points);
-        let (h17, w17) = get_vals(Node::B75RRF, points);
-        let (h18, w18) = get_vals(Node::B75RRC, points);
-        let (h19, w19) = get_vals(Node::B100, points);
-        let (h20, w20) = get_vals(Node::B100F, points);
-        let (h21, w21) = get_vals(Node::B100C, points);
-        let (h22, w22) = get_vals(Node::B100R, points);
-        let (h23, w23) = get_vals(Node::B100RF, points);
-        let (h24, w24) = get_vals(Node::B100RC, points);
-        let (h25, w25) = get_vals(Node::B100RA, points);
-        let (h26, w26) = get_vals(Node::B100RRF, points);
-        let (h27, w27) = get_vals(Node::B100RRC, points);
-        let (h28, w28) = get_vals(Node::XX, points);
-        let (h29, w29) = get_vals(Node::XB50, points);
-        let (h30, w30) = get_vals(Node::XB50F, points);
-        let (h31, w31) = get_vals(Node::XB50C, points);
-        let (h32, w32) = get_vals(Node::XB50R, points);
-        let (h33, w33) = get_vals(Node::XB50RF, points);
-        let (h34, w34) = get_vals(Node::XB50RC, points);
-        let (h35, w35) = get_vals(Node::XB50RA, points);
-        let (h36, w36) = get_vals(Node::XB50RRF, points);
-        let (h37, w37) = get_vals(Node::XB50RRC, points);
-        let (h38, w38) = get_vals(Node::XB75, points);
-        let (h39, w39) = get_vals(Node::XB75F, points);
-        let (h40, w40) = get_vals(Node::XB75C, points);
-        let (h41, w41) = get_vals(Node::XB75R, points);
-        let (h42, w42) = get_vals(Node::XB75RF, points);
-        let (h43, w43) = get_vals(Node::XB75RC, points);
-        let (h44, w44) = get_vals(Node::XB75RA, points);
-        let (h45, w45) = get_vals(Node::XB75RRF, points);
-        let (h46, w46) = get_vals(Node::XB75RRC, points);
-        let (h47, w47) = get_vals(Node::XB100, points);
-        let (h48, w48) = get_vals(Node::XB100F, points);
-        let (h49, w49) = get_vals(Node::XB100C, points);
-        let (h50, w50) = get_vals(Node::XB100R, points);
-        let (h51, w51) = get_vals(Node::XB100RF, points);
-        let (h52, w52) = get_vals(Node::XB100RC, points);
-        let (h53, w53) = get_vals(Node::XB100RA, points);
-        let (h54, w54) = get_vals(Node::XB100RRF, points);
-        let (h55, w55) = get_vals(Node::XB100RRC, points);
-        let (h56, w56) = get_vals(Node::X, points);
-        println!(
-            "\n   
-                                B50F [h: {h2}, w: {w2}]		
-	B50 [h: {h1}, w: {w1}]      B50C [h: {h3}, w: {w3}]				
-		                        B50R [h: {h4}, w: {w4}]     B50RF [h: {h5}, w: {w5}] 		
-			                                                B50RC [h: {h6}, w: {w6}] 		
-			                                                B50RA [h: {h7}, w: {w7}]    B50RRF [h: {h8}, w: {w8}] 	
-				                                                                        B50RRC [h: {h9}, w: {w9}] 	
-		                        B75F [h: {h10}, w: {w10}] 			
-N	B75 [h: {h11}, w: {w11}] 	B75C [h: {h12}, w: {w12}] 			
-		                        B75R [h: {h13}, w: {w13}] 	B75RF [h: {h14}, w: {w14}]		
-			                                                B75RC [h: {h15}, w: {w15}]		
-			                                                B75RA [h: {h16}, w: {w16}]	B75RRF [h: {h17}, w: {w17}]	
-				                                                                        B75RRC [h: {h18}, w: {w18}]	
-	                            B100F [h: {h20}, w: {w20}]		
-	B100 [h: {h19}, w: {w19}]   B100C [h: {h21}, w: {w21}]				
-		                        B100R [h: {h22}, w: {w22}]  B100RF [h: {h23}, w: {w23}] 		
-			                                                B100RC [h: {h24}, w: {w24}] 		
-			                                                B100RA [h: {h25}, w: {w25}] B100RRF [h: {h26}, w: {w26}] 	
-				                                                                        B100RRC [h: {h27}, w: {w27}]
-    ");
-        println!(
-        "\n   
-                            XX [h: {h28}, w: {w28}] 
-                            
-                                                        XB50F [h: {h30}, w: {w30}]		
-                            XB50 [h: {h29}, w: {w29}]   XB50C [h: {h31}, w: {w31}]				
-                                                        XB50R [h: {h32}, w: {w32}]      XB50RF [h: {h33}, w: {w33}] 		
-                                                                                        XB50RC [h: {h34}, w: {w34}] 		
-                                                                                        XB50RA [h: {h35}, w: {w35}]     XB50RRF [h: {h36}, w: {w36}] 	
-                                                                                                                        XB50RRC [h: {h37}, w: {w37}] 	
-                                                        XB75F [h: {h39}, w: {w39}] 			
-    X[h: {h56}, w: {w56}]	XB75 [h: {h38}, w: {w38}] 	XB75C [h: {h40}, w: {w40}] 			
-                                                        XB75R [h: {h41}, w: {w41}] 	    XB75RF [h: {h42}, w: {w42}]		
-                                                                                        XB75RC [h: {h43}, w: {w43}]		
-                                                                                        XB75RA [h: {h44}, w: {w44}]	    XB75RRF [h: {h45}, w: {w45}]	
-                                                                                                                        XB75RRC [h: {h46}, w: {w46}]	
-                                                        XB100F [h: {h48}, w: {w48}]		
-                            XB100 [h: {h47}, w: {w47}]  XB100C [h: {h49}, w: {w49}]				
-                                                        XB100R [h: {h50}, w: {w50}]     XB100RF [h: {h51}, w: {w51}] 		
-                                                                                        XB100RC [h: {h52}, w: {w52}] 		
-                                                                                        XB100RA [h: {h53}, w: {w53}]    XB100RRF [h: {h54}, w: {w54}] 	
-                                                                                                                        XB100RRC [h: {h55}, w: {w55}]
-");
-    }
-    pub fn calc_graph(branches: &Vec<Branch>) -> Vec<Self> {
-        todo!()
+    }
+    // Частоты нод после prev (None - первое действие). Если в графе частот нет, то по винрейтам.
+    pub fn distribution(points: &[GraphPoint], prev: Option<Node>) -> Strategy {
+        let nodes = possible_nodes(prev);
+        let freqs = nodes
+            .iter()
+            .map(|&node| {
+                points
+                    .iter()
+                    .find(|p| p.node == node)
+                    .map_or(0.0, |p| p.freq.to_f64().unwrap_or_default())
+            })
+            .collect::<Vec<f64>>();
+        let sum = freqs.iter().sum::<f64>();
+        if sum > 0.0 {
+            nodes
+                .into_iter()
+                .zip(freqs.iter().map(|f| f / sum))
+                .collect()
+        } else {
+            Self::distribution_from_wins(points, prev)
+        }
+    }
+    pub fn sample_node(points: &[GraphPoint], prev: Option<Node>, rng: &mut GameRng) -> Node {
+        sample(&Self::distribution(points, prev), rng).unwrap_or_else(|| unreachable!())
+    }
+    fn distribution_from_wins(points: &[GraphPoint], prev: Option<Node>) -> Strategy {
+        let nodes = possible_nodes(prev);
+        let winrates = nodes
+            .iter()
+            .map(|&node| match points.iter().find(|p| p.node == node) {
+                Some(p) if p.hands != 0 => (
+                    (p.win / Decimal::from(p.hands))
+                        .to_f64()
+                        .unwrap_or_default(),
+                    p.hands as f64,
+                ),
+                _ => (0.0, 0.0),
+            })
+            .collect::<Vec<_>>();
+        nodes
+            .into_iter()
+            .zip(frequencies_from_winrates(&winrates))
+            .collect()
+    }
+}
+
+fn possible_nodes(prev: Option<Node>) -> Vec<Node> {
+    match prev {
+        Some(node) => node.childrens(),
+        None => Node::start_nodes(),
     }
 }
 
-fn get_vals(node: Node, points: &Vec<GraphPoint>) -> (usize, Decimal) {
+fn get_vals(node: Node, points: &[GraphPoint]) -> (usize, Decimal) {
     points
         .iter()
-        .find_map(|&p| {
-            if p.node == node {
-                Some((p.hands, p.win))
-            } else {
-                None
-            }
-        })
-        .unwrap()
+        .find(|p| p.node == node)
+        .map_or((0, Decimal::ZERO), |p| (p.hands, p.win))
 }
 
-#[derive(
-    Debug,
-    PartialEq,
-    PartialOrd,
-    Eq,
-    Ord,
-    Hash,
-    Clone,
-    Copy,
-    EnumIter,
-    EnumString,
-    Serialize,
-    Deserialize,
-)]
-pub enum Node {
-    // 57
-    X,
-    XX,
-    XB50,
-    XB75,
-    XB100,
-    XB50F,
-    XB50C,
-    XB50R,
-    XB75F,
-    XB75C,
-    XB75R,
-    XB100F,
-    XB100C,
-    XB100R,
-    XB50RF,
-    XB50RC,
-    XB50RA,
-    XB75RF,
-    XB75RC,
-    XB75RA,
-    XB100RF,
-    XB100RC,
-    XB100RA,
-    XB50RRF,
-    XB75RRF,
-    XB100RRF,
-    XB50RRC,
-    XB75RRC,
-    XB100RRC,
-    N,
-    B50,
-    B75,
-    B100,
-    B50F,
-    B50C,
-    B50R,
-    B75F,
-    B75C,
-    B75R,
-    B100F,
-    B100C,
-    B100R,
-    B50RF,
-    B50RC,
-    B50RA,
-    B75RF,
-    B75RC,
-    B75RA,
-    B100RF,
-    B100RC,
-    B100RA,
-    B50RRF,
-    B75RRF,
-    B100RRF,
-    B50RRC,
-    B75RRC,
-    B100RRC,
+// Ставка в процентах банка или максимальный рейз (как раньше RA: самый большой рейз из возможных).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum BetSize {
+    Pot(u16),
+    Max,
 }
-impl Node {
-    pub fn parent(&self) -> Option<Node> {
-        let all_branches = Branch::all_branches();
-        let branch = all_branches
-            .into_iter()
-            .find(|br| br.path.contains(self))
-            .unwrap();
-        let index = branch.path.iter().position(|n| n == self).unwrap();
-        if index == 0 {
-            None
-        } else {
-            Some(*branch.path.get(index).unwrap())
+impl BetSize {
+    // Часть имени ноды: у максимального рейза пусто, как в старых именах B50R, B50RA.
+    fn label(&self) -> String {
+        match self {
+            BetSize::Pot(percent) => percent.to_string(),
+            BetSize::Max => String::new(),
         }
     }
-    pub fn childrens(&self) -> Vec<Node> {
-        let mut result = vec![];
-        let all_branches = Branch::all_branches();
-        for branch in all_branches.into_iter().filter(|br| br.path.contains(self)) {
-            if branch.last_node() == *self {
-                continue;
+}
+
+/*
+Настройка дерева ривера:
+- bet_sizes: ставки первого и ставки после чека в процентах банка.
+- raise_sizes: рейзы на ставку и ре-рейзы.
+- max_raises: сколько рейзов можно после ставки. После последнего только фолд или колл.
+- allin_threshold: доля стека. Ставка или рейз, который забирает не меньше этой доли, играется
+  олл-ином (или максимальным рейзом, если олл-ина нет в возможных действиях). None - не меняется.
+- variant: в пот-лимите (омахи) ставки и рейзы больше 100% банка запрещены. В старых конфигах
+  его нет - Plo4.
+Ставка или рейз больше самого большого рейза из возможных действий (пот или стек) играется им.
+По умолчанию старое дерево: 50/75/100, рейз и ре-рейз максимальные.
+*/
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct TreeConfig {
+    pub bet_sizes: Vec<u16>,
+    pub raise_sizes: Vec<BetSize>,
+    pub max_raises: u8,
+    pub allin_threshold: Option<Decimal>,
+    #[serde(default)]
+    pub variant: GameVariant,
+}
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self {
+            bet_sizes: vec![50, 75, 100],
+            raise_sizes: vec![BetSize::Max],
+            max_raises: 2,
+            allin_threshold: None,
+            variant: GameVariant::Plo4,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum TreeAction {
+    Root,
+    Check,
+    Bet(u16),
+    Fold,
+    Call,
+    Raise(BetSize),
+}
+
+#[derive(Debug, Clone)]
+struct TreeNode {
+    name: String,
+    action: TreeAction,
+    parent: Option<Node>,
+    children: Vec<Node>,
+    depth: usize,
+}
+
+/*
+Дерево действий ривера хедз-ап, которое строится из TreeConfig. Нода - номер в дереве, корень N (0).
+Имена как у старых нод: ставка B50, после чека XB50, ответы F и C, первый рейз R, следующие
+рейзы A с предыдущими R перед ними (B50R, B50RA), ответы на k рейзов - k раз R и F или C (B50RRF).
+У рейзов в процентах банка размер после буквы: B50R100, B50R100A50, B50R100R50F.
+*/
+#[derive(Debug, Clone)]
+pub struct ActionTree {
+    pub config: TreeConfig,
+    nodes: Vec<TreeNode>,
+    by_name: HashMap<String, Node>,
+}
+impl ActionTree {
+    pub fn new(config: TreeConfig) -> Result<Self, String> {
+        if config.bet_sizes.contains(&0) || config.raise_sizes.contains(&BetSize::Pot(0)) {
+            return Err(String::from("Error: bet size must be more than 0% of pot"));
+        }
+        let over_pot = config.bet_sizes.iter().any(|&size| size > 100)
+            || config
+                .raise_sizes
+                .iter()
+                .any(|&size| matches!(size, BetSize::Pot(percent) if percent > 100));
+        if over_pot && !config.variant.is_no_limit() {
+            return Err(String::from(
+                "Error: bet size over 100% of pot in pot-limit tree",
+            ));
+        }
+        if let Some(threshold) = config.allin_threshold {
+            if threshold <= Decimal::ZERO || threshold > Decimal::ONE {
+                return Err(format!(
+                    "Error: all-in threshold must be from 0 to 1, got {}",
+                    threshold
+                ));
+            }
+        }
+        let mut tree = Self {
+            config,
+            nodes: vec![],
+            by_name: HashMap::new(),
+        };
+        let root = tree.add(None, String::from("N"), TreeAction::Root)?;
+        for size in tree.config.bet_sizes.clone() {
+            tree.add_bet(root, "", size)?;
+        }
+        let check = tree.add(Some(root), String::from("X"), TreeAction::Check)?;
+        tree.add(Some(check), String::from("XX"), TreeAction::Check)?;
+        for size in tree.config.bet_sizes.clone() {
+            tree.add_bet(check, "X", size)?;
+        }
+        Ok(tree)
+    }
+    fn add(
+        &mut self,
+        parent: Option<Node>,
+        name: String,
+        action: TreeAction,
+    ) -> Result<Node, String> {
+        if self.by_name.contains_key(&name) {
+            return Err(format!("Error: node {} is in the tree twice", name));
+        }
+        let id = u16::try_from(self.nodes.len())
+            .map_err(|_| String::from("Error: too many nodes in the tree"))?;
+        let node = Node(id);
+        let depth = parent.map_or(0, |p| self.nodes[p.0 as usize].depth + 1);
+        if let Some(p) = parent {
+            self.nodes[p.0 as usize].children.push(node);
+        }
+        self.by_name.insert(name.clone(), node);
+        self.nodes.push(TreeNode {
+            name,
+            action,
+            parent,
+            children: vec![],
+            depth,
+        });
+        Ok(node)
+    }
+    fn add_bet(&mut self, parent: Node, prefix: &str, size: u16) -> Result<(), String> {
+        let bet_name = format!("{}B{}", prefix, size);
+        let bet = self.add(Some(parent), bet_name.clone(), TreeAction::Bet(size))?;
+        self.add_responses(bet, &bet_name, &[])
+    }
+    // Ответы на ставку или рейз. raises - размеры рейзов после ставки bet_name.
+    fn add_responses(
+        &mut self,
+        node: Node,
+        bet_name: &str,
+        raises: &[String],
+    ) -> Result<(), String> {
+        let made = raises
+            .iter()
+            .map(|label| format!("R{}", label))
+            .collect::<String>();
+        self.add(
+            Some(node),
+            format!("{}{}F", bet_name, made),
+            TreeAction::Fold,
+        )?;
+        self.add(
+            Some(node),
+            format!("{}{}C", bet_name, made),
+            TreeAction::Call,
+        )?;
+        if raises.len() >= self.config.max_raises as usize {
+            return Ok(());
+        }
+        for size in self.config.raise_sizes.clone() {
+            let label = size.label();
+            let name = if raises.is_empty() {
+                format!("{}R{}", bet_name, label)
             } else {
-                let index = branch.path.iter().position(|n| n == self).unwrap();
-                let node = *branch.path.get(index + 1).unwrap();
-                if !result.contains(&node) {
-                    result.push(node);
+                format!("{}{}A{}", bet_name, made, label)
+            };
+            let raise = self.add(Some(node), name, TreeAction::Raise(size))?;
+            let mut next = raises.to_vec();
+            next.push(label);
+            self.add_responses(raise, bet_name, &next)?;
+        }
+        Ok(())
+    }
+    pub fn root(&self) -> Node {
+        Node(0)
+    }
+    // Все ноды с корнем, родитель всегда раньше детей.
+    pub fn nodes(&self) -> Vec<Node> {
+        (0..self.nodes.len() as u16).map(Node).collect()
+    }
+    pub fn node(&self, name: &str) -> Option<Node> {
+        self.by_name.get(name).copied()
+    }
+    pub fn name(&self, node: Node) -> &str {
+        &self.info(node).name
+    }
+    pub fn action(&self, node: Node) -> TreeAction {
+        self.info(node).action
+    }
+    pub fn parent(&self, node: Node) -> Option<Node> {
+        self.info(node).parent
+    }
+    pub fn children(&self, node: Node) -> &[Node] {
+        &self.info(node).children
+    }
+    // Сколько действий сделано до ноды включительно, у корня 0.
+    pub fn depth(&self, node: Node) -> usize {
+        self.info(node).depth
+    }
+    // Все ветки от первого действия до последнего, в порядке дерева.
+    pub fn branches(&self) -> Vec<Branch> {
+        self.nodes()
+            .into_iter()
+            .filter(|&node| node != self.root() && self.children(node).is_empty())
+            .map(|leaf| {
+                let mut path = vec![leaf];
+                while let Some(parent) = self.parent(*path.last().unwrap_or_else(|| unreachable!()))
+                {
+                    if parent == self.root() {
+                        break;
+                    }
+                    path.push(parent);
                 }
+                path.reverse();
+                Branch::new(path)
+            })
+            .collect()
+    }
+    pub fn action_kind(
+        &self,
+        node: Node,
+        game: &impl Game,
+        position: Position,
+        possible_act: &Vec<ActionKind>,
+    ) -> ActionKind {
+        let stack = game.player_by_position_as_ref(position).stack_size;
+        let max_raise = possible_act
+            .iter()
+            .filter_map(|&act| match act {
+                ActionKind::Raise(val) => Some(val),
+                _ => None,
+            })
+            .max();
+        // Размер из дерева, но не больше самого большого рейза из возможных и стека.
+        let sized = |amount: Decimal| match max_raise {
+            None => find_max_raise(possible_act),
+            Some(max_raise) if amount >= max_raise || amount >= stack => {
+                ActionKind::Raise(max_raise)
+            }
+            Some(_) => match self.config.allin_threshold {
+                Some(threshold) if amount >= threshold * stack => find_allin(possible_act, stack),
+                _ => ActionKind::Raise(amount),
+            },
+        };
+        match self.action(node) {
+            TreeAction::Root => unreachable!(),
+            TreeAction::Check => ActionKind::Check,
+            TreeAction::Fold => ActionKind::Fold,
+            TreeAction::Call => find_call_not_aicall(possible_act),
+            TreeAction::Bet(percent) => {
+                sized((Decimal::from(percent) / dec!(100) * game.main_pot().value).round_dp(0))
+            }
+            TreeAction::Raise(BetSize::Max) => find_max_raise(possible_act),
+            TreeAction::Raise(BetSize::Pot(percent)) => {
+                // Как сайзинги в action::possible_action_kind: доля банка после колла.
+                let max_commit = max_current_commit_from_all(game);
+                let my_commit = already_commit_by_pos(game, position);
+                let pot_after_call = game.winnable_pot(position) - my_commit + max_commit;
+                sized(
+                    max_commit + (Decimal::from(percent) / dec!(100) * pot_after_call).round_dp(0),
+                )
             }
         }
-        result
+    }
+    fn info(&self, node: Node) -> &TreeNode {
+        &self.nodes[node.0 as usize]
+    }
+}
+
+static RIVER_TREE: OnceLock<ActionTree> = OnceLock::new();
+
+// Дерево, по которому играются и хранятся все ноды. Если не задано set_river_tree - по умолчанию.
+pub fn river_tree() -> &'static ActionTree {
+    RIVER_TREE
+        .get_or_init(|| ActionTree::new(TreeConfig::default()).unwrap_or_else(|_| unreachable!()))
+}
+// Свое дерево задается один раз до первой ноды: ноды - номера в дереве, и поменять его потом нельзя.
+pub fn set_river_tree(config: TreeConfig) -> Result<(), String> {
+    let tree = ActionTree::new(config)?;
+    RIVER_TREE
+        .set(tree)
+        .map_err(|_| String::from("Error: river tree is already in use"))
+}
+
+// Нода дерева river_tree. В файлах и Debug - имя ноды.
+#[derive(PartialEq, PartialOrd, Eq, Ord, Hash, Clone, Copy)]
+pub struct Node(u16);
+impl Node {
+    pub fn root() -> Node {
+        river_tree().root()
+    }
+    pub fn all() -> Vec<Node> {
+        river_tree().nodes()
+    }
+    pub fn name(&self) -> &'static str {
+        river_tree().name(*self)
+    }
+    // Для первых действий None, как prev_node при розыгрыше.
+    pub fn parent(&self) -> Option<Node> {
+        river_tree()
+            .parent(*self)
+            .filter(|&parent| parent != Node::root())
+    }
+    pub fn childrens(&self) -> Vec<Node> {
+        river_tree().children(*self).to_vec()
     }
     pub fn action_from_node(
         node: Node,
-        v_pot: Decimal,
+        game: &impl Game,
+        position: Position,
         possible_act: &Vec<ActionKind>,
     ) -> ActionKind {
-        match node {
-            Node::X => ActionKind::Check,
-            Node::XX => ActionKind::Check,
-            Node::XB50 | Node::B50 => ActionKind::Raise((dec!(0.5) * v_pot).round_dp(0)),
-            Node::XB75 | Node::B75 => ActionKind::Raise((dec!(0.75) * v_pot).round_dp(0)),
-            Node::XB100 | Node::B100 => ActionKind::Raise((dec!(1.) * v_pot).round_dp(0)),
-            Node::XB50F
-            | Node::XB75F
-            | Node::XB100F
-            | Node::B50F
-            | Node::B75F
-            | Node::B100F
-            | Node::XB50RF
-            | Node::XB75RF
-            | Node::XB100RF
-            | Node::B50RF
-            | Node::B75RF
-            | Node::B100RF
-            | Node::B50RRF
-            | Node::B75RRF
-            | Node::B100RRF
-            | Node::XB50RRF
-            | Node::XB75RRF
-            | Node::XB100RRF => ActionKind::Fold,
-            Node::XB50C
-            | Node::B50C
-            | Node::XB75C
-            | Node::B75C
-            | Node::XB100C
-            | Node::B100C
-            | Node::XB50RC
-            | Node::B50RC
-            | Node::XB75RC
-            | Node::B75RC
-            | Node::XB100RC
-            | Node::B100RC
-            | Node::B50RRC
-            | Node::B75RRC
-            | Node::B100RRC
-            | Node::XB50RRC
-            | Node::XB75RRC
-            | Node::XB100RRC => find_call_not_aicall(&possible_act),
-            Node::XB50R
-            | Node::B50R
-            | Node::XB75R
-            | Node::B75R
-            | Node::XB100R
-            | Node::B100R
-            | Node::XB50RA
-            | Node::B50RA
-            | Node::XB75RA
-            | Node::B75RA
-            | Node::XB100RA
-            | Node::B100RA => find_max_raise(possible_act),
-            Node::N => unreachable!(),
-        }
+        river_tree().action_kind(node, game, position, possible_act)
     }
-    #[allow(unused_variables)]
     pub fn all_branches_from_node(node: &Node) -> Vec<Branch> {
-        todo!()
+        river_tree()
+            .branches()
+            .into_iter()
+            .filter(|branch| branch.path.contains(node))
+            .collect()
     }
     pub fn is_last_node(&self) -> bool {
-        self.childrens().is_empty()
+        river_tree().children(*self).is_empty()
     }
     pub fn start_nodes() -> Vec<Node> {
-        vec![Node::B50, Node::B75, Node::B100, Node::X]
+        Node::root().childrens()
+    }
+}
+impl Debug for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+impl FromStr for Node {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        river_tree()
+            .node(s)
+            .ok_or_else(|| format!("Error: no node {} in river tree", s))
+    }
+}
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Node::from_str(&name).map_err(de::Error::custom)
+    }
+}
+
+fn find_allin(possible_act: &Vec<ActionKind>, stack: Decimal) -> ActionKind {
+    if possible_act.contains(&ActionKind::Raise(stack)) {
+        ActionKind::Raise(stack)
+    } else {
+        find_max_raise(possible_act)
     }
 }
 
@@ -420,126 +563,192 @@ impl Branch {
         *self.path.first().unwrap()
     }
     pub fn all_branches() -> HashSet<Branch> {
-        let mut result = HashSet::with_capacity(43);
-        let br = Branch::new(vec![Node::B50, Node::B50F]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B50, Node::B50C]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B50, Node::B50R, Node::B50RF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B50, Node::B50R, Node::B50RC]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B50, Node::B50R, Node::B50RA, Node::B50RRF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B50, Node::B50R, Node::B50RA, Node::B50RRC]);
-        result.insert(br);
+        river_tree().branches().into_iter().collect()
+    }
+}
 
-        let br = Branch::new(vec![Node::B75, Node::B75F]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B75, Node::B75C]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B75, Node::B75R, Node::B75RF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B75, Node::B75R, Node::B75RC]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B75, Node::B75R, Node::B75RA, Node::B75RRF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B75, Node::B75R, Node::B75RA, Node::B75RRC]);
-        result.insert(br);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{action, seeded_rng, PostflopGame, PreflopGame};
 
-        let br = Branch::new(vec![Node::B100, Node::B100F]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B100, Node::B100C]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B100, Node::B100R, Node::B100RF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B100, Node::B100R, Node::B100RC]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B100, Node::B100R, Node::B100RA, Node::B100RRF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::B100, Node::B100R, Node::B100RA, Node::B100RRC]);
-        result.insert(br);
+    fn node(name: &str) -> Node {
+        name.parse().unwrap()
+    }
+    fn point(node: Node, hands: usize, win: Decimal) -> GraphPoint {
+        GraphPoint {
+            hands,
+            win,
+            ..GraphPoint::new(node)
+        }
+    }
+    #[test]
+    fn default_tree_is_old_node_tree() {
+        let tree = river_tree();
+        assert_eq!(tree.nodes().len(), 57);
+        assert_eq!(Branch::all_branches().len(), 37);
+        let names = |nodes: Vec<Node>| nodes.iter().map(|n| n.name()).collect::<Vec<_>>();
+        assert_eq!(names(Node::start_nodes()), ["B50", "B75", "B100", "X"]);
+        assert_eq!(
+            names(node("X").childrens()),
+            ["XX", "XB50", "XB75", "XB100"]
+        );
+        assert_eq!(names(node("B50R").childrens()), ["B50RF", "B50RC", "B50RA"]);
+        assert_eq!(names(node("XB75RA").childrens()), ["XB75RRF", "XB75RRC"]);
+        assert_eq!(node("B50RA").parent(), Some(node("B50R")));
+        assert_eq!(node("B50").parent(), None);
+        assert!(node("XX").is_last_node() && !node("X").is_last_node());
+        assert!(Branch::new(vec![node("X"), node("XB100"), node("XB100C")]).is_correct());
+        assert_eq!(Node::all_branches_from_node(&node("B75R")).len(), 4);
+        assert!("B60".parse::<Node>().is_err());
+
+        // Имена в файлах графов те же, что у старого перечисления.
+        let json = serde_json::to_string(&GraphPoint::new(node("XB100RRC"))).unwrap();
+        assert!(json.contains(r#""node":"XB100RRC""#));
+        let old: GraphPoint =
+            serde_json::from_str(r#"{"node":"B75RA","hands":3,"win":"1.5"}"#).unwrap();
+        assert_eq!(old.node, node("B75RA"));
+        assert_eq!(old.freq, Decimal::ZERO);
+    }
+    #[test]
+    fn tree_from_config() {
+        let config = TreeConfig {
+            bet_sizes: vec![33, 150],
+            raise_sizes: vec![BetSize::Pot(100), BetSize::Max],
+            max_raises: 1,
+            allin_threshold: Some(dec!(0.5)),
+            variant: GameVariant::Holdem,
+        };
+        let tree = ActionTree::new(config.clone()).unwrap();
+        // Корень, X, XX и по 2 ставки с 2 ответами и 2 рейзами с 2 ответами.
+        assert_eq!(tree.nodes().len(), 3 + 4 * (1 + 2 + 2 * 3));
+        let b33 = tree.node("B33").unwrap();
+        let names = tree
+            .children(b33)
+            .iter()
+            .map(|&n| tree.name(n))
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["B33F", "B33C", "B33R100", "B33R"]);
+        let raise = tree.node("XB150R100").unwrap();
+        assert_eq!(tree.action(raise), TreeAction::Raise(BetSize::Pot(100)));
+        assert!(tree.node("XB150R100F").is_some());
+        assert!(tree.node("XB150R100A100").is_none());
+        assert_eq!(tree.depth(tree.node("XB150RC").unwrap()), 4);
+        assert_eq!(tree.branches().len(), 1 + 4 * (2 + 2 * 2));
 
-        let br = Branch::new(vec![Node::X, Node::XX]);
-        result.insert(br);
+        let two_raises = ActionTree::new(TreeConfig {
+            max_raises: 2,
+            ..config.clone()
+        })
+        .unwrap();
+        assert!(two_raises.node("B33R100A100").is_some());
+        assert!(two_raises.node("B33R100R100C").is_some());
+
+        let twice = TreeConfig {
+            bet_sizes: vec![50, 50],
+            ..TreeConfig::default()
+        };
+        assert!(ActionTree::new(twice).is_err());
+        let zero = TreeConfig {
+            bet_sizes: vec![0],
+            ..TreeConfig::default()
+        };
+        assert!(ActionTree::new(zero).is_err());
+        // Больше пота только в безлимите.
+        let pot_limit = TreeConfig {
+            variant: GameVariant::Plo4,
+            ..config.clone()
+        };
+        assert!(ActionTree::new(pot_limit).is_err());
+    }
+    #[test]
+    fn tree_actions_with_allin_threshold() {
+        let mut rng = seeded_rng(2);
+        let preflop = PreflopGame::new_with_lock_cards_and_rng(&vec![], &mut rng);
+        let game = PostflopGame::from_with_rng(&preflop, &mut rng);
+        let position = Position::Sb;
+        let possible_act = action::possible_action_kind(&game, position);
+        let pot = game.main_pot.value;
 
-        let br = Branch::new(vec![Node::X, Node::XB50, Node::XB50F]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB50, Node::XB50C]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB50, Node::XB50R, Node::XB50RF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB50, Node::XB50R, Node::XB50RC]);
-        result.insert(br);
-        let br = Branch::new(vec![
-            Node::X,
-            Node::XB50,
-            Node::XB50R,
-            Node::XB50RA,
-            Node::XB50RRF,
-        ]);
-        result.insert(br);
-        let br = Branch::new(vec![
-            Node::X,
-            Node::XB50,
-            Node::XB50R,
-            Node::XB50RA,
-            Node::XB50RRC,
-        ]);
-        result.insert(br);
+        let tree = river_tree();
+        assert_eq!(
+            tree.action_kind(node("B50"), &game, position, &possible_act),
+            ActionKind::Raise((dec!(0.5) * pot).round_dp(0))
+        );
+        assert_eq!(
+            tree.action_kind(node("X"), &game, position, &possible_act),
+            ActionKind::Check
+        );
 
-        let br = Branch::new(vec![Node::X, Node::XB75, Node::XB75F]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB75, Node::XB75C]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB75, Node::XB75R, Node::XB75RF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB75, Node::XB75R, Node::XB75RC]);
-        result.insert(br);
-        let br = Branch::new(vec![
-            Node::X,
-            Node::XB75,
-            Node::XB75R,
-            Node::XB75RA,
-            Node::XB75RRF,
-        ]);
-        result.insert(br);
-        let br = Branch::new(vec![
-            Node::X,
-            Node::XB75,
-            Node::XB75R,
-            Node::XB75RA,
-            Node::XB75RRC,
-        ]);
-        result.insert(br);
+        let stack = game.player_by_position_as_ref(position).stack_size;
+        let max_raise = *possible_act
+            .iter()
+            .find(|act| matches!(act, ActionKind::Raise(_)))
+            .unwrap();
 
-        let br = Branch::new(vec![Node::X, Node::XB100, Node::XB100F]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB100, Node::XB100C]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB100, Node::XB100R, Node::XB100RF]);
-        result.insert(br);
-        let br = Branch::new(vec![Node::X, Node::XB100, Node::XB100R, Node::XB100RC]);
-        result.insert(br);
-        let br = Branch::new(vec![
-            Node::X,
-            Node::XB100,
-            Node::XB100R,
-            Node::XB100RA,
-            Node::XB100RRF,
-        ]);
-        result.insert(br);
-        let br = Branch::new(vec![
-            Node::X,
-            Node::XB100,
-            Node::XB100R,
-            Node::XB100RA,
-            Node::XB100RRC,
-        ]);
-        result.insert(br);
+        // Ставка 50% банка не меньше 0.5% стека - олл-ин или максимальный рейз.
+        let allin = ActionTree::new(TreeConfig {
+            allin_threshold: Some(dec!(0.005)),
+            ..TreeConfig::default()
+        })
+        .unwrap();
+        let act = allin.action_kind(allin.node("B50").unwrap(), &game, position, &possible_act);
+        assert!(act == ActionKind::Raise(stack) || act == max_raise);
 
-        assert_eq!(result.len(), 37);
+        // Без порога ставка 500% банка в пот-лимитной игре - самый большой возможный рейз.
+        let over_pot = ActionTree::new(TreeConfig {
+            bet_sizes: vec![500],
+            raise_sizes: vec![BetSize::Pot(500)],
+            variant: GameVariant::Holdem,
+            ..TreeConfig::default()
+        })
+        .unwrap();
+        let bet = over_pot.node("B500").unwrap();
+        assert_eq!(
+            over_pot.action_kind(bet, &game, position, &possible_act),
+            max_raise
+        );
+        let ActionKind::Raise(amount) = max_raise else {
+            unreachable!()
+        };
+        assert!(amount <= stack);
+    }
+    #[test]
+    fn graph_frequencies_and_sampling() {
+        let mut points = GraphPoint::get_all_graph_points();
+        for p in [
+            point(node("X"), 100, dec!(50)),
+            point(node("B100"), 100, dec!(20)),
+            point(node("B50"), 100, dec!(-30)),
+            point(node("XX"), 10, dec!(5)),
+        ] {
+            *points.iter_mut().find(|x| x.node == p.node).unwrap() = p;
+        }
+        // Без частот (старый файл графа) они считаются по винрейтам.
+        let from_wins = GraphPoint::distribution(&points, None);
+        GraphPoint::set_frequencies(&mut points);
+        for ((node, stored), (same, freq)) in GraphPoint::distribution(&points, None)
+            .into_iter()
+            .zip(from_wins)
+        {
+            assert_eq!(node, same);
+            assert!((stored - freq).abs() < 1e-4);
+        }
+        let freq = |name| points.iter().find(|p| p.node == node(name)).unwrap().freq;
+        // Средний винрейт 40 / 300: лучше него X (0.5) и B100 (0.2).
+        assert_eq!(freq("X"), dec!(0.8462));
+        assert_eq!(freq("B100"), dec!(0.1538));
+        assert_eq!(freq("B50"), Decimal::ZERO);
+        assert_eq!(freq("XX"), dec!(1));
 
-        result
+        let mut rng = seeded_rng(5);
+        let bets = (0..1_000)
+            .filter(|_| GraphPoint::sample_node(&points, None, &mut rng) == node("B100"))
+            .count();
+        assert!((100..210).contains(&bets));
+        assert_eq!(
+            GraphPoint::sample_node(&points, Some(node("X")), &mut rng),
+            node("XX")
+        );
     }
 }
@@ -0,0 +1,310 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+/*
+Вариант игры:
+- Омахи (Plo4, Plo5) пот-лимит, на вскрытии ровно 2 карты из руки и 3 с борда.
+- Холдем (Holdem) безлимитный, 2 карманные карты, лучшие 5 из 7 в любом сочетании.
+- Хай-лоу омахи (Plo4HiLo, Plo5HiLo): каждый банк делится пополам между лучшим хаем и лучшим
+  лоу 8-or-better. Лоу тоже ровно 2 из руки и 3 с борда. Нет лоу - весь банк хаю.
+*/
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum GameVariant {
+    #[default]
+    Plo4,
+    Plo5,
+    Holdem,
+    Plo4HiLo,
+    Plo5HiLo,
+}
+impl GameVariant {
+    pub fn hole_cards(&self) -> usize {
+        match self {
+            Self::Plo4 | Self::Plo4HiLo => 4,
+            Self::Plo5 | Self::Plo5HiLo => 5,
+            Self::Holdem => 2,
+        }
+    }
+    pub fn is_omaha(&self) -> bool {
+        !matches!(self, Self::Holdem)
+    }
+    pub fn is_hi_lo(&self) -> bool {
+        matches!(self, Self::Plo4HiLo | Self::Plo5HiLo)
+    }
+    pub fn is_no_limit(&self) -> bool {
+        matches!(self, Self::Holdem)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub enum Straddle {
+    Utg,
+    Button,
+}
+
+/*
+Структура блайндов:
+- sb, bb - блайнды, идут в ставки улицы (positions_and_money).
+- ante - анте с каждого игрока, bb_ante - анте за весь стол от ББ. Анте это мертвые деньги в банке,
+  в ставки улицы они не идут, но учитываются во вкладе игрока для сайд-потов.
+- straddle - страддл 2bb. Страддлер ходит префлоп последним, а минимальный рейз считается от страддла.
+*/
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct BlindsConfig {
+    pub sb: Decimal,
+    pub bb: Decimal,
+    pub ante: Decimal,
+    pub bb_ante: Decimal,
+    pub straddle: Option<Straddle>,
+}
+impl Default for BlindsConfig {
+    fn default() -> Self {
+        Self {
+            sb: dec!(0.5),
+            bb: dec!(1),
+            ante: Decimal::ZERO,
+            bb_ante: Decimal::ZERO,
+            straddle: None,
+        }
+    }
+}
+impl BlindsConfig {
+    pub fn straddle_size(&self) -> Decimal {
+        self.bb * dec!(2)
+    }
+}
+
+/*
+Настройка стола: от хедз-апа (2) до фулл-ринга (9).
+- Блайнды всегда Sb и Bb. В хедз-апе Sb это баттон: он первый префлоп и последний постфлоп.
+- Остальные места от первого действующего: первый всегда Utg, дальше места с конца списка
+  Utg1, Utg2, Lj, Mp, Co, Btn. Для 6-max это Utg, Mp, Co, Btn как и было.
+- Раздача заканчивается, когда сфолдили все, кроме одного (seats - 1).
+*/
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+pub struct TableConfig {
+    seats: u8,
+    #[serde(default)]
+    blinds: BlindsConfig,
+    #[serde(default)]
+    variant: GameVariant,
+}
+impl Default for TableConfig {
+    fn default() -> Self {
+        Self {
+            seats: 6,
+            blinds: BlindsConfig::default(),
+            variant: GameVariant::default(),
+        }
+    }
+}
+impl TableConfig {
+    pub fn new(seats: u8) -> Result<Self, String> {
+        if (2..=9).contains(&seats) {
+            Ok(Self {
+                seats,
+                ..Self::default()
+            })
+        } else {
+            Err(format!(
+                "Error: table size must be from 2 to 9, got {}",
+                seats
+            ))
+        }
+    }
+    pub fn heads_up() -> Self {
+        Self {
+            seats: 2,
+            ..Self::default()
+        }
+    }
+    pub fn with_variant(mut self, variant: GameVariant) -> Self {
+        self.variant = variant;
+        self
+    }
+    pub fn with_blinds(mut self, blinds: BlindsConfig) -> Result<Self, String> {
+        if blinds.sb <= Decimal::ZERO || blinds.bb < blinds.sb {
+            return Err(String::from("Error: need 0 < sb <= bb"));
+        }
+        if blinds.ante < Decimal::ZERO || blinds.bb_ante < Decimal::ZERO {
+            return Err(String::from("Error: ante can't be negative"));
+        }
+        if blinds.straddle.is_some() && self.is_heads_up() {
+            return Err(String::from("Error: no straddle in heads-up"));
+        }
+        self.blinds = blinds;
+        Ok(self)
+    }
+    pub fn seats(&self) -> u8 {
+        self.seats
+    }
+    pub fn blinds(&self) -> BlindsConfig {
+        self.blinds
+    }
+    pub fn variant(&self) -> GameVariant {
+        self.variant
+    }
+    pub fn straddle_position(&self) -> Option<Position> {
+        // Utg страддл это первый после ББ. За столом на троих это тот же баттон.
+        match self.blinds.straddle {
+            Some(Straddle::Utg) => self.not_blinds().first().copied(),
+            Some(Straddle::Button) => Some(self.button()),
+            None => None,
+        }
+    }
+    // Сколько позиция ставит в начале префлопа без учета анте.
+    pub fn posted_blind(&self, position: Position) -> Decimal {
+        if self.straddle_position() == Some(position) {
+            return self.blinds.straddle_size();
+        }
+        match position {
+            Position::Sb => self.blinds.sb,
+            Position::Bb => self.blinds.bb,
+            _ => Decimal::ZERO,
+        }
+    }
+    pub fn posted_ante(&self, position: Position) -> Decimal {
+        match position {
+            Position::Bb => self.blinds.ante + self.blinds.bb_ante,
+            _ => self.blinds.ante,
+        }
+    }
+    // Минимальный рейз в начале префлопа.
+    pub fn preflop_min_bet(&self) -> Decimal {
+        match self.blinds.straddle {
+            Some(_) => self.blinds.straddle_size(),
+            None => self.blinds.bb,
+        }
+    }
+    pub fn is_heads_up(&self) -> bool {
+        self.seats == 2
+    }
+    pub fn button(&self) -> Position {
+        if self.is_heads_up() {
+            Position::Sb
+        } else {
+            Position::Btn
+        }
+    }
+    pub fn preflop_order(&self) -> Vec<Position> {
+        // Со страддлом круг тот же, но начинается после страддлера, и он ходит последним.
+        let mut order = self.not_blinds();
+        order.extend_from_slice(&[Position::Sb, Position::Bb]);
+        if let Some(straddler) = self.straddle_position() {
+            let index = order
+                .iter()
+                .position(|&pos| pos == straddler)
+                .unwrap_or_else(|| unreachable!());
+            order.rotate_left(index + 1);
+        }
+        order
+    }
+    pub fn postflop_order(&self) -> Vec<Position> {
+        if self.is_heads_up() {
+            return vec![Position::Bb, Position::Sb];
+        }
+        let mut order = vec![Position::Sb, Position::Bb];
+        order.extend(self.not_blinds());
+        order
+    }
+    // Все позиции стола в порядке enum Position.
+    pub fn positions(&self) -> Vec<Position> {
+        let mut positions = self.not_blinds();
+        positions.extend_from_slice(&[Position::Sb, Position::Bb]);
+        positions.sort_unstable();
+        positions
+    }
+    pub fn contains(&self, position: Position) -> bool {
+        self.positions().contains(&position)
+    }
+    pub fn everyone_else_folded(&self, folded_count: usize) -> bool {
+        folded_count + 1 >= self.seats as usize
+    }
+    fn not_blinds(&self) -> Vec<Position> {
+        let count = self.seats as usize - 2;
+        let tail = [
+            Position::Utg1,
+            Position::Utg2,
+            Position::Lj,
+            Position::Mp,
+            Position::Co,
+            Position::Btn,
+        ];
+        match count {
+            0 => vec![],
+            1 => vec![Position::Btn],
+            _ => {
+                let mut v = vec![Position::Utg];
+                v.extend_from_slice(&tail[tail.len() - (count - 1)..]);
+                v
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn six_max_is_default_positions() {
+        let table = TableConfig::default();
+        assert_eq!(table.postflop_order(), Position::all_poses());
+        assert_eq!(
+            table.preflop_order(),
+            vec![
+                Position::Utg,
+                Position::Mp,
+                Position::Co,
+                Position::Btn,
+                Position::Sb,
+                Position::Bb
+            ]
+        );
+        assert!(table.everyone_else_folded(5));
+        assert!(!table.everyone_else_folded(4));
+    }
+    #[test]
+    fn heads_up_and_full_ring_order() {
+        let hu = TableConfig::heads_up();
+        assert_eq!(hu.preflop_order(), vec![Position::Sb, Position::Bb]);
+        assert_eq!(hu.postflop_order(), vec![Position::Bb, Position::Sb]);
+        assert_eq!(hu.button(), Position::Sb);
+        assert!(hu.everyone_else_folded(1));
+
+        let full_ring = TableConfig::new(9).unwrap();
+        assert_eq!(full_ring.preflop_order().len(), 9);
+        assert_eq!(full_ring.preflop_order()[1], Position::Utg1);
+        assert_eq!(full_ring.postflop_order().last(), Some(&Position::Btn));
+        assert_eq!(
+            TableConfig::new(3).unwrap().preflop_order(),
+            vec![Position::Btn, Position::Sb, Position::Bb]
+        );
+        assert!(TableConfig::new(1).is_err());
+        assert!(TableConfig::new(10).is_err());
+    }
+    #[test]
+    fn straddle_acts_last_preflop() {
+        let blinds = BlindsConfig {
+            straddle: Some(Straddle::Utg),
+            ..BlindsConfig::default()
+        };
+        let table = TableConfig::default().with_blinds(blinds).unwrap();
+        assert_eq!(table.preflop_order()[0], Position::Mp);
+        assert_eq!(table.preflop_order().last(), Some(&Position::Utg));
+        assert_eq!(table.posted_blind(Position::Utg), dec!(2));
+        assert_eq!(table.preflop_min_bet(), dec!(2));
+
+        let blinds = BlindsConfig {
+            straddle: Some(Straddle::Button),
+            ..BlindsConfig::default()
+        };
+        let table = TableConfig::default().with_blinds(blinds).unwrap();
+        assert_eq!(table.preflop_order()[0], Position::Sb);
+        assert_eq!(table.preflop_order().last(), Some(&Position::Btn));
+        assert!(TableConfig::heads_up().with_blinds(blinds).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+// Общие заготовки для тестов разных модулей.
+use rust_decimal_macros::dec;
+
+use crate::hand::parse_cards;
+use crate::{ActionKind, Card, Game, Hand, Position, PreflopGame};
+
+// Utg all-in 10, Mp all-in 50, Co call 50, Btn/Sb/Bb fold.
+// Банки: главный 31.5 (Utg, Mp, Co), сайд 80 (Mp, Co).
+pub(crate) fn preflop_with_short_allins() -> PreflopGame {
+    let mut game = PreflopGame::new();
+    game.player_by_position_as_mut_ref(Position::Utg).stack_size = dec!(10);
+    game.player_by_position_as_mut_ref(Position::Mp).stack_size = dec!(50);
+    game.player_by_position_as_mut_ref(Position::Co).stack_size = dec!(100);
+    game.do_action_on_position(Some(ActionKind::Raise(dec!(10))), Position::Utg);
+    game.do_action_on_position(Some(ActionKind::Raise(dec!(50))), Position::Mp);
+    game.do_action_on_position(Some(ActionKind::Call(dec!(50))), Position::Co);
+    game.do_action_on_position(Some(ActionKind::Fold), Position::Btn);
+    game.do_action_on_position(Some(ActionKind::Fold), Position::Sb);
+    game.do_action_on_position(Some(ActionKind::Fold), Position::Bb);
+    game
+}
+
+// Карты и рука из строки, как с распознавателя: "As Kd", "AsKdQhJc". Пустая строка - нет карт.
+pub(crate) fn cards(s: &str) -> Vec<Card> {
+    if s.trim().is_empty() {
+        return vec![];
+    }
+    parse_cards(s).unwrap()
+}
+pub(crate) fn hand(s: &str) -> Hand {
+    s.parse().unwrap()
+}